@@ -1,4 +1,4 @@
-use std::{borrow::Cow, marker::PhantomData};
+use std::{borrow::Cow, cell::OnceCell, marker::PhantomData};
 
 use strum::VariantArray;
 
@@ -6,7 +6,7 @@ use crate::bitboard::{
     board::BitBoard,
     moving::clone_make_pseudolegal_move,
     utils::SliceExtensions,
-    vision::{Panopticon, Vision},
+    vision::{Panopticon, PieceVision, Vision},
 };
 use crate::model::*;
 
@@ -23,6 +23,12 @@ pub trait AttackMaskGenerator<'a, BB: BitBoard> {
     fn attacks(&self, board: &'a BB, color: ChessColor) -> Attacks;
 
     fn attacks_after(&self, board: &'a BB, color: ChessColor, mv: ChessMove) -> Attacks;
+
+    /// Whether the chessmen of `color` give check after the move is made.
+    #[inline]
+    fn check_after(&self, board: &'a BB, color: ChessColor, mv: ChessMove) -> bool {
+        self.attacks_after(board, color, mv).check()
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -54,17 +60,7 @@ where
     }
 
     fn attacks(&self, board: &BB, player: ChessColor) -> Attacks {
-        let pan = X::new(board.total());
-        match player {
-            ChessColor::WHITE => Attacks {
-                attack: attacks_from_echarray_white(pan, &board.side(ChessColor::WHITE)),
-                targeted_king: board.men(ChessColor::BLACK, ChessPiece::KING),
-            },
-            ChessColor::BLACK => Attacks {
-                attack: attacks_from_echarray_black(pan, &board.side(ChessColor::WHITE)),
-                targeted_king: board.men(ChessColor::WHITE, ChessPiece::KING),
-            },
-        }
+        attacks::<BB, X>(board, player)
     }
 
     fn attacks_after(&self, board: &'a BB, color: ChessColor, mv: ChessMove) -> Attacks {
@@ -73,23 +69,617 @@ where
     }
 }
 
+/// Attack mask strategy which computes the attack fill of each color at most once
+/// per position, and tests the legality of moves by looking only along the lines
+/// through the king which the move changes.
+pub struct CachedAttackStrategy<X: Panopticon>(PhantomData<X>);
+
+pub struct CachedAttacks<BB: BitBoard, X: Panopticon> {
+    fills: [OnceCell<Attacks>; 2],
+    _phantom: PhantomData<(BB, X)>,
+}
+
+impl<BB: BitBoard, X: Panopticon> CachedAttacks<BB, X> {
+    /// The number of full attack fills computed so far.
+    pub fn fills(&self) -> usize {
+        self.fills.iter().filter(|f| f.get().is_some()).count()
+    }
+}
+
+impl<X: Panopticon> AttackMaskStrategy for CachedAttackStrategy<X> {
+    type CachedData<'a, BB: BitBoard + 'a> = CachedAttacks<BB, X>;
+}
+
+impl<'a, BB, X> AttackMaskGenerator<'a, BB> for CachedAttacks<BB, X>
+where
+    BB: BitBoard + 'a,
+    X: Panopticon,
+{
+    fn new(board: &'a BB) -> Self {
+        CachedAttacks {
+            fills: [OnceCell::new(), OnceCell::new()],
+            _phantom: PhantomData,
+        }
+    }
+
+    fn attacks(&self, board: &'a BB, color: ChessColor) -> Attacks {
+        *self.fills[color.ix()].get_or_init(|| attacks::<BB, X>(board, color))
+    }
+
+    fn attacks_after(&self, board: &'a BB, color: ChessColor, mv: ChessMove) -> Attacks {
+        let new_board = clone_make_pseudolegal_move(board, PseudoLegal(mv));
+        attacks::<BB, X>(&new_board, color)
+    }
+
+    fn check_after(&self, board: &'a BB, color: ChessColor, mv: ChessMove) -> bool {
+        // The rook moves as well, but castling is rare enough to do it the slow way
+        if CastlingDirection::from_special(mv.special).is_some() {
+            return self.attacks_after(board, color, mv).check();
+        }
+
+        let mover = board.ply().0;
+        let king = if mv.ech == ChessPiece::KING && color != mover {
+            mv.to
+        } else {
+            let king = board.men(color.opp(), ChessPiece::KING);
+            if king == 0 {
+                return false;
+            }
+            Square::from_u8(king.trailing_zeros() as u8)
+        };
+
+        let to = 1 << mv.to.ix();
+        let mut occupancy = board.total() & !(1 << mv.from.ix()) | to;
+
+        if mv.capture.is_some()
+            && mv.special == Some(SpecialMove::PAWN)
+            && let Some(ep) = board.trans().en_passant
+        {
+            occupancy &= !(1 << ep.capture.ix());
+        }
+
+        // A chessman captured on the destination square attacks nothing
+        let mut attackers = attackers_within::<BB, X>(board, king, color, occupancy) & !to;
+
+        // The moved chessman attacks from its destination, possibly promoted
+        if color == mover {
+            use ChessPiece::*;
+            let pan = X::new(occupancy);
+            let ech = PawnPromotion::from_special(mv.special).map_or(mv.ech, ChessPiece::from);
+            attackers |= to
+                & match (ech, color) {
+                    (PAWN, ChessColor::WHITE) => pan.black_pawn().see(king),
+                    (PAWN, ChessColor::BLACK) => pan.white_pawn().see(king),
+                    (KNIGHT, _) => pan.knight().see(king),
+                    (BISHOP, _) => pan.bishop().see(king),
+                    (ROOK, _) => pan.rook().see(king),
+                    (QUEEN, _) => pan.queen().see(king),
+                    (KING, _) => 0,
+                };
+        }
+
+        attackers != 0
+    }
+}
+
+#[test]
+fn cached_attack_strategy_test() {
+    use crate::{
+        bitboard::{
+            board::{ChessBoard, FullerBitBoard},
+            hash::FullZobristTables,
+            movegen::{LegalBlessing, NoBlessing, enumerate},
+            perft::{MakeUnmake, perft, perft_types},
+            vision::MostlyBits,
+        },
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    type Cached = LegalBlessing<CachedAttackStrategy<MostlyBits>>;
+    type ZT = FullZobristTables;
+
+    let res = perft::<FullerBitBoard, MostlyBits, Cached, MakeUnmake, ZT>(4, false, false, ());
+    assert_eq!(res.nodes(), 197281);
+
+    for (fen, nodes) in [
+        (
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            97862,
+        ),
+        ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 2812),
+        (
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            9467,
+        ),
+    ] {
+        let board = FenBoard::parser()
+            .parse(fen)
+            .into_result()
+            .unwrap()
+            .as_bitboard::<FullerBitBoard, ZT>();
+        let types = perft_types::<_, MostlyBits, Cached, ZT>(&board, 3);
+        assert_eq!(types.nodes, nodes, "{fen}");
+    }
+
+    let board = FullerBitBoard::startpos::<ZT>();
+    let cache = CachedAttacks::<_, MostlyBits>::new(&board);
+    let mut moves = vec![];
+    enumerate::<_, MostlyBits, NoBlessing>(&board, &mut moves);
+    for mv in moves {
+        cache.check_after(&board, ChessColor::BLACK, mv.0);
+        cache.attacks(&board, ChessColor::BLACK);
+    }
+    assert_eq!(cache.fills(), 1);
+}
+
+/// Compute the squares attacked by the chessmen of one color, along with
+/// the position of the opposing king.
+pub fn attacks<BB: BitBoard, X: Panopticon>(board: &BB, player: ChessColor) -> Attacks {
+    let pan = X::new(board.total());
+    match player {
+        ChessColor::WHITE => Attacks {
+            attack: attacks_from_echarray_white(pan, &board.side(ChessColor::WHITE)),
+            targeted_king: board.men(ChessColor::BLACK, ChessPiece::KING),
+        },
+        ChessColor::BLACK => Attacks {
+            attack: attacks_from_echarray_black(pan, &board.side(ChessColor::BLACK)),
+            targeted_king: board.men(ChessColor::WHITE, ChessPiece::KING),
+        },
+    }
+}
+
+/// Compute the chessmen of the opposing color which give check to the king of `player`.
+pub fn checkers<BB: BitBoard, X: Panopticon>(board: &BB, player: ChessColor) -> u64 {
+    let king = board.men(player, ChessPiece::KING);
+    if king == 0 {
+        return 0;
+    }
+
+    attackers::<BB, X>(
+        board,
+        Square::from_u8(king.trailing_zeros() as u8),
+        player.opp(),
+    )
+}
+
+/// The pin ray of the chessman on `sq`, if it is pinned against its own king:
+/// the squares between the king and the pinning slider, including the slider
+/// and the pinned chessman itself. Zero if the chessman is not pinned.
+pub fn pin_ray<BB: BitBoard, X: Panopticon>(board: &BB, sq: Square) -> u64 {
+    let bit = 1 << sq.ix();
+    let color = if board.color(ChessColor::WHITE) & bit != 0 {
+        ChessColor::WHITE
+    } else if board.color(ChessColor::BLACK) & bit != 0 {
+        ChessColor::BLACK
+    } else {
+        return 0;
+    };
+
+    let king = board.men(color, ChessPiece::KING);
+    if king == 0 || king & bit != 0 {
+        return 0;
+    }
+    let king = Square::from_u8(king.trailing_zeros() as u8);
+
+    let enemy = color.opp();
+    let queens = board.men(enemy, ChessPiece::QUEEN);
+    let with = X::new(board.total());
+    let without = X::new(board.total() & !bit);
+
+    pin_ray_along(
+        with.rook(),
+        without.rook(),
+        king,
+        bit,
+        board.men(enemy, ChessPiece::ROOK) | queens,
+    ) | pin_ray_along(
+        with.bishop(),
+        without.bishop(),
+        king,
+        bit,
+        board.men(enemy, ChessPiece::BISHOP) | queens,
+    )
+}
+
+/// The pin ray along the lines of one kind of slider, see [`pin_ray`].
+///
+/// Removing the pinned chessman reveals the pinner to the king, and the squares
+/// seen by both the king and the pinner are exactly those between them.
+fn pin_ray_along(
+    with: impl PieceVision,
+    without: impl PieceVision,
+    king: Square,
+    bit: u64,
+    sliders: u64,
+) -> u64 {
+    let seen = with.see(king);
+    if seen & bit == 0 {
+        return 0;
+    }
+
+    let pinner = without.see(king) & !seen & sliders;
+    if pinner == 0 {
+        return 0;
+    }
+
+    let between = without.see(king) & without.see(Square::from_u8(pinner.trailing_zeros() as u8));
+    between | pinner | bit
+}
+
+/// The squares a pinned chessman on `sq` may not move to, as they would
+/// expose its king, being everything off the pin ray. A pinned knight can
+/// never stay on the pin ray, so it has no legal move at all.
+///
+/// Zero if the chessman is not pinned.
+pub fn illegal_targets_for_pinned<BB: BitBoard, X: Panopticon>(board: &BB, sq: Square) -> u64 {
+    let ray = pin_ray::<BB, X>(board, sq);
+    if ray == 0 {
+        0
+    } else if board.piece_at(sq) == Some(ChessPiece::KNIGHT) {
+        !(1 << sq.ix())
+    } else {
+        !ray
+    }
+}
+
+/// Compute the chessmen of one color which attack a square.
+///
+/// Chessmen attacking through other chessmen, such as a rook behind a queen
+/// on the same file, are not included.
+pub fn attackers<BB: BitBoard, X: Panopticon>(board: &BB, sq: Square, color: ChessColor) -> u64 {
+    attackers_within::<BB, X>(board, sq, color, board.total())
+}
+
+/// Like [`attackers`], but only considering the chessmen on the squares of `occupancy`.
+///
+/// Removing chessmen from the occupancy reveals the attacks of those behind them,
+/// as used by static exchange evaluation.
+pub fn attackers_within<BB: BitBoard, X: Panopticon>(
+    board: &BB,
+    sq: Square,
+    color: ChessColor,
+    occupancy: u64,
+) -> u64 {
+    use ChessPiece::*;
+
+    let pan = X::new(occupancy);
+    let men = board.side(color).map(|mask| mask & occupancy);
+
+    // A pawn attacks a square exactly when a pawn of
+    // the opposing color on that square would attack it
+    let pawns = match color {
+        ChessColor::WHITE => pan.black_pawn().see(sq),
+        ChessColor::BLACK => pan.white_pawn().see(sq),
+    };
+
+    (pawns & men[PAWN.ix()])
+        | (pan.knight().see(sq) & men[KNIGHT.ix()])
+        | (pan.bishop().see(sq) & (men[BISHOP.ix()] | men[QUEEN.ix()]))
+        | (pan.rook().see(sq) & (men[ROOK.ix()] | men[QUEEN.ix()]))
+        | (pan.king().see(sq) & men[KING.ix()])
+}
+
+/// Whether any chessman of one color attacks a square.
+///
+/// Cheaper than [`attackers`], as it stops at the first echelon found attacking.
+pub fn is_attacked<BB: BitBoard, X: Panopticon>(board: &BB, sq: Square, color: ChessColor) -> bool {
+    use ChessPiece::*;
+
+    let pan = X::new(board.total());
+    let men = board.side(color);
+
+    let pawns = match color {
+        ChessColor::WHITE => pan.black_pawn().see(sq),
+        ChessColor::BLACK => pan.white_pawn().see(sq),
+    };
+
+    pawns & men[PAWN.ix()] != 0
+        || pan.knight().see(sq) & men[KNIGHT.ix()] != 0
+        || pan.bishop().see(sq) & (men[BISHOP.ix()] | men[QUEEN.ix()]) != 0
+        || pan.rook().see(sq) & (men[ROOK.ix()] | men[QUEEN.ix()]) != 0
+        || pan.king().see(sq) & men[KING.ix()] != 0
+}
+
+/// Whether the king of `color` is currently in check, without making any move.
+pub fn king_in_check<BB: BitBoard, X: Panopticon>(board: &BB, color: ChessColor) -> bool {
+    let king = board.men(color, ChessPiece::KING);
+    king != 0
+        && is_attacked::<BB, X>(
+            board,
+            Square::from_u8(king.trailing_zeros() as u8),
+            color.opp(),
+        )
+}
+
+/// Whether a pawn of the active player can capture en-passant.
+///
+/// X-FEN only writes the en-passant square when this holds.
+pub fn en_passant_is_capturable<BB: BitBoard, X: Panopticon>(board: &BB) -> bool {
+    let Some(ep) = board.trans().en_passant else {
+        return false;
+    };
+
+    let player = board.ply().0;
+    let pan = X::new(board.total());
+    let capturers = match player {
+        ChessColor::WHITE => pan.black_pawn().see(ep.square),
+        ChessColor::BLACK => pan.white_pawn().see(ep.square),
+    };
+
+    capturers & board.men(player, ChessPiece::PAWN) != 0
+}
+
+/// The squares the king of `color` may legally move to, castling aside.
+///
+/// The enemy attacks are computed with the king removed from the board,
+/// so that a slider checking the king also covers the square behind it.
+pub fn king_escape_squares<BB: BitBoard, X: Panopticon>(board: &BB, color: ChessColor) -> u64 {
+    let king = board.men(color, ChessPiece::KING);
+    if king == 0 {
+        return 0;
+    }
+
+    let attacked = enemy_attacks_xray_king::<BB, X>(board, color);
+
+    X::new(board.total())
+        .king()
+        .see(Square::from_u8(king.trailing_zeros() as u8))
+        & !board.color(color)
+        & !attacked
+}
+
+/// Compute the squares attacked by the opponent of `defender`, with the king
+/// of `defender` removed from the occupancy.
+///
+/// A checking slider keeps attacking the squares behind the king on its ray,
+/// which the king therefore cannot escape to.
+pub fn enemy_attacks_xray_king<BB: BitBoard, X: Panopticon>(
+    board: &BB,
+    defender: ChessColor,
+) -> u64 {
+    let pan = X::new(board.total() & !board.men(defender, ChessPiece::KING));
+    let enemy = board.side(defender.opp());
+    match defender.opp() {
+        ChessColor::WHITE => attacks_from_echarray_white(pan, &enemy),
+        ChessColor::BLACK => attacks_from_echarray_black(pan, &enemy),
+    }
+}
+
+#[test]
+fn enemy_attacks_xray_king_test() {
+    use crate::bitboard::{
+        board::FullerBitBoard, hash::FullZobristTables, setup::parse_position, vision::MostlyBits,
+    };
+
+    let (board, _, _) =
+        parse_position::<FullerBitBoard, FullZobristTables>("4r1k1/8/8/8/4K3/8/8/8 w - - 0 1")
+            .unwrap();
+
+    let behind = 1 << Square::e3.ix();
+    assert_ne!(
+        enemy_attacks_xray_king::<_, MostlyBits>(&board, ChessColor::WHITE) & behind,
+        0
+    );
+    assert_eq!(
+        attacks::<_, MostlyBits>(&board, ChessColor::BLACK).attack & behind,
+        0
+    );
+    assert_eq!(
+        king_escape_squares::<_, MostlyBits>(&board, ChessColor::WHITE) & behind,
+        0
+    );
+}
+
+#[test]
+fn king_escape_squares_test() {
+    use crate::bitboard::{
+        board::FullerBitBoard, hash::FullZobristTables, setup::parse_position, vision::MostlyBits,
+    };
+
+    let (board, _, _) =
+        parse_position::<FullerBitBoard, FullZobristTables>("4r1k1/8/8/8/4K3/8/8/8 w - - 0 1")
+            .unwrap();
+
+    let expected = [Square::d3, Square::d4, Square::d5, Square::f3, Square::f4, Square::f5]
+        .iter()
+        .fold(0, |acc, sq| acc | 1 << sq.ix());
+    assert_eq!(
+        king_escape_squares::<_, MostlyBits>(&board, ChessColor::WHITE),
+        expected
+    );
+
+    let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    )
+    .unwrap();
+    assert_eq!(
+        king_escape_squares::<_, MostlyBits>(&board, ChessColor::BLACK),
+        0
+    );
+}
+
+#[test]
+fn en_passant_is_capturable_test() {
+    use crate::{
+        bitboard::{board::FullerBitBoard, hash::FullZobristTables, vision::MostlyBits},
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    let capturable = |fen: &str| {
+        let board = FenBoard::parser()
+            .parse(fen)
+            .into_result()
+            .unwrap()
+            .as_bitboard::<FullerBitBoard, FullZobristTables>();
+        en_passant_is_capturable::<_, MostlyBits>(&board)
+    };
+
+    assert!(capturable("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2"));
+    assert!(capturable("4k3/8/8/8/5Pp1/8/8/4K3 b - f3 0 1"));
+    assert!(!capturable("4k3/8/8/3p4/8/8/4P3/4K3 w - d6 0 2"));
+    assert!(!capturable("4k3/8/8/3p4/4P3/8/8/4K3 w - d6 0 2"));
+    assert!(!capturable("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 2"));
+}
+
+#[test]
+fn king_in_check_test() {
+    use crate::{
+        bitboard::{
+            board::FullerBitBoard,
+            hash::FullZobristTables,
+            movegen::{LegalBlessing, enumerate},
+            moving::clone_make_legal_move,
+            vision::MostlyBits,
+        },
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let board = FenBoard::parser()
+        .parse("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+        .into_result()
+        .unwrap()
+        .as_bitboard::<FullerBitBoard, FullZobristTables>();
+
+    let mut boards = vec![board];
+    let mut moves = vec![];
+    let mut checks = 0;
+
+    for _ in 0..2 {
+        let mut next = vec![];
+        for board in &boards {
+            enumerate::<_, MostlyBits, Legal>(board, &mut moves);
+            for mv in moves.iter().copied() {
+                next.push(clone_make_legal_move::<_, FullZobristTables>(board, mv));
+            }
+        }
+        boards = next;
+
+        for board in &boards {
+            for color in [ChessColor::WHITE, ChessColor::BLACK] {
+                let fake = attacks::<_, MostlyBits>(board, color.opp()).check();
+                assert_eq!(king_in_check::<_, MostlyBits>(board, color), fake);
+                checks += fake as usize;
+            }
+        }
+    }
+
+    assert!(checks > 0);
+}
+
 #[inline]
 fn attacks_from_echarray_pieces<X: Panopticon>(pan: X, echs: &[u64; 6]) -> u64 {
     use ChessPiece::*;
 
     pan.knight().surveil(echs[KNIGHT.ix()])
-        ^ pan.bishop().surveil(echs[BISHOP.ix()])
-        ^ pan.rook().surveil(echs[ROOK.ix()])
-        ^ pan.queen().surveil(echs[QUEEN.ix()])
-        ^ pan.king().surveil(echs[KING.ix()])
+        | pan.bishop().surveil(echs[BISHOP.ix()])
+        | pan.rook().surveil(echs[ROOK.ix()])
+        | pan.queen().surveil(echs[QUEEN.ix()])
+        | pan.king().surveil(echs[KING.ix()])
 }
 
 #[inline]
 fn attacks_from_echarray_black<X: Panopticon>(pan: X, echs: &[u64; 6]) -> u64 {
-    pan.black_pawn().surveil(echs[ChessPiece::PAWN.ix()]) ^ attacks_from_echarray_pieces(pan, echs)
+    pan.black_pawn().surveil(echs[ChessPiece::PAWN.ix()]) | attacks_from_echarray_pieces(pan, echs)
 }
 
 #[inline]
 fn attacks_from_echarray_white<X: Panopticon>(pan: X, echs: &[u64; 6]) -> u64 {
-    pan.white_pawn().surveil(echs[ChessPiece::PAWN.ix()]) ^ attacks_from_echarray_pieces(pan, echs)
+    pan.white_pawn().surveil(echs[ChessPiece::PAWN.ix()]) | attacks_from_echarray_pieces(pan, echs)
+}
+
+#[test]
+fn checkers_test() {
+    use crate::bitboard::{
+        board::FullerBitBoard, hash::FullZobristTables, setup::parse_position, vision::MostlyBits,
+    };
+
+    let checkers = |fen| {
+        let (board, player, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+        checkers::<_, MostlyBits>(&board, player)
+    };
+
+    assert_eq!(
+        checkers("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+        0
+    );
+
+    // A single check by a pawn, or a bishop
+    assert_eq!(
+        checkers("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1"),
+        1 << Square::d2.ix()
+    );
+    assert_eq!(
+        checkers("4k3/8/8/8/1b6/8/8/4K3 w - - 0 1"),
+        1 << Square::b4.ix()
+    );
+    assert_eq!(checkers("4k3/8/8/8/8/3P4/8/4K3 w - - 0 1"), 0);
+
+    // A double check by a knight and a rook
+    assert_eq!(
+        checkers("4k3/8/8/8/8/5n2/8/r3K3 w - - 0 1"),
+        1 << Square::f3.ix() | 1 << Square::a1.ix()
+    );
+
+    // The rook behind the queen checks only through it
+    assert_eq!(
+        checkers("4r3/4q3/8/8/8/8/8/4K2k w - - 0 1"),
+        1 << Square::e7.ix()
+    );
+}
+
+#[test]
+fn pinned_test() {
+    use crate::bitboard::{
+        board::FullerBitBoard, hash::FullZobristTables, setup::parse_position, vision::MostlyBits,
+    };
+
+    let pinned = |fen, sq: Square| {
+        let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+        (
+            pin_ray::<_, MostlyBits>(&board, sq),
+            illegal_targets_for_pinned::<_, MostlyBits>(&board, sq),
+        )
+    };
+
+    let file = [Square::e2, Square::e3, Square::e4, Square::e5, Square::e6, Square::e7]
+        .iter()
+        .fold(0, |acc, sq| acc | 1 << sq.ix());
+
+    // A knight pinned along the file can never move
+    let (ray, illegal) = pinned("4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1", Square::e2);
+    assert_eq!(ray, file);
+    assert_eq!(illegal, !(1 << Square::e2.ix()));
+
+    // A rook pinned along the file can move along it
+    let (ray, illegal) = pinned("4k3/4r3/8/8/8/8/4R3/4K1N1 w - - 0 1", Square::e2);
+    assert_eq!(ray, file);
+    assert_eq!(illegal, !file);
+
+    // A black bishop pinned along the diagonal by a queen
+    let diagonal = [Square::d7, Square::c6, Square::b5, Square::a4]
+        .iter()
+        .fold(0, |acc, sq| acc | 1 << sq.ix());
+    let (ray, illegal) = pinned("4k3/3b4/8/8/Q7/8/8/4K3 w - - 0 1", Square::d7);
+    assert_eq!(ray, diagonal);
+    assert_eq!(illegal, !diagonal);
+
+    // Not pinned: another chessman blocks, the slider is of the wrong kind,
+    // or the square is empty
+    assert_eq!(
+        pinned("4k3/4r3/8/8/4P3/8/4N3/4K3 w - - 0 1", Square::e2),
+        (0, 0)
+    );
+    assert_eq!(
+        pinned("4k3/4b3/8/8/8/8/4N3/4K3 w - - 0 1", Square::e2),
+        (0, 0)
+    );
+    assert_eq!(
+        pinned("4k3/4r3/8/8/8/8/8/4K3 w - - 0 1", Square::e2),
+        (0, 0)
+    );
 }
@@ -3,7 +3,10 @@
 /// Here be ~~dragons~~ SIMD instructions.
 use std::simd::{num::SimdUint, u64x2, u64x4};
 
-use crate::bitboard::vision::{PawnVision, PawnsBitBlit, Vision};
+use crate::bitboard::{
+    utils::{FILES, RANKS},
+    vision::{PawnVision, PawnsBitBlit, Vision},
+};
 
 use crate::model::*;
 
@@ -55,16 +58,15 @@ pub fn bishop_diff_obs_simdx2(sq: Square, total: u64) -> u64 {
 /// Compute all squares attacked by white pawns at once using bit operations.
 #[inline]
 pub fn white_pawn_attack_fill(mask: u64) -> u64 {
-    mask << 7 & !0x8080_8080_8080_8080 | mask << 9 & !0x0101_0101_0101_0101
+    mask << 7 & !FILES[7] | mask << 9 & !FILES[0]
 }
 
 /// Compute all squares attacked by white pawns at once using bit operations, in
 /// parallel using simd operations. Probably not faster than [`white_pawn_attack_fill`].
 #[inline]
 pub fn white_pawn_attack_fill_simdx2(mask: u64) -> u64 {
-    (u64x2::splat(mask) << u64x2::from_array([7, 9])
-        & u64x2::from_array([!0x8080_8080_8080_8080, !0x0101_0101_0101_0101]))
-    .reduce_or()
+    (u64x2::splat(mask) << u64x2::from_array([7, 9]) & u64x2::from_array([!FILES[7], !FILES[0]]))
+        .reduce_or()
 }
 
 /// Advance all white pawns at once, using bit operations.
@@ -76,16 +78,15 @@ pub fn white_pawn_advance_fill(mask: u64, empty: u64) -> u64 {
 /// Compute all squares attacked by black pawns at once using bit operations.
 #[inline]
 pub fn black_pawn_attack_fill(mask: u64) -> u64 {
-    mask >> 7 & !0x0101_0101_0101_0101 | mask >> 9 & !0x8080_8080_8080_8080
+    mask >> 7 & !FILES[0] | mask >> 9 & !FILES[7]
 }
 
 /// Compute all squares attacked by black pawns at once using bit operations, in
 /// parallel using simd operations. Probably not faster than [`black_pawn_attack_fill`].
 #[inline]
 pub fn black_pawn_attack_fill_simdx2(mask: u64) -> u64 {
-    (u64x2::splat(mask) >> u64x2::from_array([7, 9])
-        & u64x2::from_array([!0x0101_0101_0101_0101, !0x8080_8080_8080_8080]))
-    .reduce_or()
+    (u64x2::splat(mask) >> u64x2::from_array([7, 9]) & u64x2::from_array([!FILES[0], !FILES[7]]))
+        .reduce_or()
 }
 
 /// Advance all black pawns at once, using bit operations.
@@ -101,18 +102,8 @@ pub fn black_pawn_advance_fill(mask: u64, empty: u64) -> u64 {
 #[inline]
 pub fn king_dumbfill_simdx4(mask: u64) -> u64 {
     let shift = u64x4::from_array([7, 8, 9, 1]);
-    let wrap_shl = u64x4::from_array([
-        !0x8080_8080_8080_8080,
-        !0,
-        !0x0101_0101_0101_0101,
-        !0x0101_0101_0101_0101,
-    ]);
-    let wrap_slr = u64x4::from_array([
-        !0x0101_0101_0101_0101,
-        !0,
-        !0x8080_8080_8080_8080,
-        !0x8080_8080_8080_8080,
-    ]);
+    let wrap_shl = u64x4::from_array([!FILES[7], !0, !FILES[0], !FILES[0]]);
+    let wrap_slr = u64x4::from_array([!FILES[0], !0, !FILES[7], !FILES[7]]);
     (u64x4::splat(mask) << shift & wrap_shl | u64x4::splat(mask) >> shift & wrap_slr).reduce_or()
 }
 
@@ -120,12 +111,8 @@ pub fn king_dumbfill_simdx4(mask: u64) -> u64 {
 #[inline]
 pub fn knight_dumbfill_simdx4(mask: u64) -> u64 {
     let shift = u64x4::from_array([6, 15, 17, 10]);
-    let wrap = u64x4::from_array([
-        !0xC0C0_C0C0_C0C0_C0C0,
-        !0x8080_8080_8080_8080,
-        !0x0101_0101_0101_0101,
-        !0x0303_0303_0303_0303,
-    ]);
+    let wrap =
+        u64x4::from_array([!0xC0C0_C0C0_C0C0_C0C0, !FILES[7], !FILES[0], !0x0303_0303_0303_0303]);
     (u64x4::splat(mask) << shift & wrap | u64x4::splat(mask) >> shift & wrap.reverse()).reduce_or()
 }
 
@@ -147,7 +134,7 @@ pub fn knight_dumbfill_simdx4(mask: u64) -> u64 {
 pub fn rook_dumb7fill_simdx2(rooks: u64, empty: u64) -> u64 {
     const SHIFT: u64x2 = u64x2::from_array([1, 8]);
 
-    const WRAP_SHL: u64x2 = u64x2::from_array([!0x0101_0101_0101_0101, !0]);
+    const WRAP_SHL: u64x2 = u64x2::from_array([!FILES[0], !0]);
     let empty_shl = u64x2::splat(empty) & WRAP_SHL;
     let mut rooks_shl = u64x2::splat(rooks);
     let mut flood_shl = u64x2::splat(0);
@@ -158,7 +145,7 @@ pub fn rook_dumb7fill_simdx2(rooks: u64, empty: u64) -> u64 {
     flood_shl |= rooks_shl << SHIFT & empty_shl;
     flood_shl = flood_shl << SHIFT & WRAP_SHL;
 
-    const WRAP_SHR: u64x2 = u64x2::from_array([!0x8080_8080_8080_8080, !0]);
+    const WRAP_SHR: u64x2 = u64x2::from_array([!FILES[7], !0]);
     let empty_shr = u64x2::splat(empty) & WRAP_SHR;
     let mut rooks_shr = u64x2::splat(rooks);
     let mut flood_shr = u64x2::splat(0);
@@ -176,7 +163,7 @@ pub fn rook_dumb7fill_simdx2(rooks: u64, empty: u64) -> u64 {
 #[inline]
 pub fn bishop_dumb7fill_simdx2(bishops: u64, empty: u64) -> u64 {
     const SHIFT: u64x2 = u64x2::from_array([7, 9]);
-    const WRAP: u64x2 = u64x2::from_array([!0x8080_8080_8080_8080, !0x0101_0101_0101_0101]);
+    const WRAP: u64x2 = u64x2::from_array([!FILES[7], !FILES[0]]);
 
     let empty_shl = u64x2::splat(empty) & WRAP;
     let mut rooks_shl = u64x2::splat(bishops);
@@ -205,18 +192,8 @@ pub fn bishop_dumb7fill_simdx2(bishops: u64, empty: u64) -> u64 {
 #[inline]
 pub fn queen_dumb7fill_simdx2(queens: u64, rooks: u64, bishops: u64, empty: u64) -> u64 {
     const SHIFT: u64x4 = u64x4::from_array([8, 7, 9, 1]);
-    const WRAP_SHL: u64x4 = u64x4::from_array([
-        !0,
-        !0x8080_8080_8080_8080,
-        !0x0101_0101_0101_0101,
-        !0x0101_0101_0101_0101,
-    ]);
-    const WRAP_SHR: u64x4 = u64x4::from_array([
-        !0,
-        !0x8080_8080_8080_8080,
-        !0x0101_0101_0101_0101,
-        !0x0101_0101_0101_0101,
-    ]);
+    const WRAP_SHL: u64x4 = u64x4::from_array([!0, !FILES[7], !FILES[0], !FILES[0]]);
+    const WRAP_SHR: u64x4 = u64x4::from_array([!0, !FILES[7], !FILES[0], !FILES[0]]);
 
     let empty_shl = u64x4::splat(empty) & WRAP_SHL;
     let mut queens_shl =
@@ -317,7 +294,7 @@ fn split(sq: Square, mask: u64) -> (u64, u64) {
 /// Computes both the `rank` and `file` functions in parallel using SIMD.
 #[inline]
 fn rank_and_file(sq: Square) -> u64x2 {
-    u64x2::from_array([0x0000_0000_0000_00FF, 0x0101_0101_0101_0101])
+    u64x2::from_array([RANKS[0], FILES[0]])
         << u64x2::from_array([sq as u64 & 0x38, sq as u64 & 0x7])
 }
 
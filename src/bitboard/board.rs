@@ -25,10 +25,13 @@ use std::borrow::Cow;
 
 use crate::bitboard::{
     castling::BitCastling,
+    eval::MATERIAL,
     hash::ZobristTables,
+    setup::parse_position,
     utils::{SliceExtensions, bitor_sum},
 };
 use crate::model::*;
+use crate::notation::fen::FenError;
 use strum::VariantArray;
 
 /// The basic operations of a bitboard.
@@ -46,6 +49,30 @@ pub trait BitBoard: ChessBoard {
     /// Determine if a chessman of some echelon stands on a square
     fn piece_at(&self, sq: Square) -> Option<ChessPiece>;
 
+    /// Determine which chessman, if any, stands on a square
+    fn man_at(&self, sq: Square) -> Option<ChessMan> {
+        let ech = self.piece_at(sq)?;
+        let color = if self.color(ChessColor::WHITE) & (1 << sq.ix()) != 0 {
+            ChessColor::WHITE
+        } else {
+            ChessColor::BLACK
+        };
+        Some(ChessMan::new(color, ech))
+    }
+
+    /// Determine whether a square is empty, or holds a chessman friendly
+    /// or hostile to `perspective`, and of which echelon
+    fn square_state(&self, sq: Square, perspective: ChessColor) -> SquareState {
+        let Some(ech) = self.piece_at(sq) else {
+            return SquareState::Empty;
+        };
+        if self.color(perspective) & (1 << sq.ix()) != 0 {
+            SquareState::Friendly(ech)
+        } else {
+            SquareState::Enemy(ech)
+        }
+    }
+
     /// Determine if a chessman of some non-king echelon stands on a square
     fn commoner_at(&self, sq: Square) -> Option<ChessCommoner> {
         self.piece_at(sq).and_then(ChessCommoner::from_piece)
@@ -59,6 +86,40 @@ pub trait BitBoard: ChessBoard {
 
     /// Retrieve the bitboard representing all occupied squares.
     fn total(&self) -> u64;
+
+    /// The total value of the material of each color, kings excluded,
+    /// using the values of [`MATERIAL`].
+    fn material_value(&self) -> [i32; 2] {
+        [ChessColor::WHITE, ChessColor::BLACK].map(|color| {
+            ChessCommoner::VARIANTS
+                .iter()
+                .map(|&ech| self.men(color, ech.into()).count_ones() as i32 * MATERIAL[ech.ix()])
+                .sum()
+        })
+    }
+}
+
+/// The contents of a square relative to one player, see [`BitBoard::square_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SquareState {
+    Empty,
+    Friendly(ChessPiece),
+    Enemy(ChessPiece),
+}
+
+/// Panic unless [`BitBoard::commoner_at`] agrees with [`BitBoard::piece_at`] on every square,
+/// being `None` exactly where there is no chessman or a king.
+///
+/// Implementations which override `commoner_at` call this from their sanity checks.
+pub fn assert_commoners_agree(board: &impl BitBoard) {
+    for ix in 0..64 {
+        let sq = Square::from_u8(ix);
+        assert_eq!(
+            board.commoner_at(sq),
+            board.piece_at(sq).and_then(ChessCommoner::from_piece),
+            "commoner_at and piece_at disagree on {sq}"
+        );
+    }
 }
 
 /// A proper chessboard.
@@ -90,6 +151,24 @@ pub trait ChessBoard: MetaBoard {
 
     /// Recompute the Zobrist hash of this table.
     fn rehash<ZT: ZobristTables>(&self) -> u64;
+
+    /// Set this board up for a new game, see [`ChessBoard::startpos`].
+    #[inline]
+    fn reset_to_startpos<ZT: ZobristTables>(&mut self)
+    where
+        Self: Sized,
+    {
+        *self = Self::startpos::<ZT>();
+    }
+
+    /// Set this board up from a position in FEN, leaving it unchanged if the FEN is invalid.
+    fn reset_to<ZT: ZobristTables>(&mut self, fen: &str) -> Result<(), FenError>
+    where
+        Self: BitBoard,
+    {
+        (*self, _, _) = parse_position::<Self, ZT>(fen)?;
+        Ok(())
+    }
 }
 
 /// The metadata associated with a chessboard.
@@ -139,6 +218,13 @@ pub trait MetaBoard: Clone {
     /// the turn number and the active player.
     fn ply(&self) -> (ChessColor, u16);
 
+    /// Current active player color and turn number, as a [`Ply`].
+    #[inline]
+    fn ply_struct(&self) -> Ply {
+        let (player, turn) = self.ply();
+        Ply(turn, player)
+    }
+
     /// Increment the ply, i.e. swap active player color and increment
     /// the turn counter if the swap was black-to-white.
     fn next_ply(&mut self);
@@ -199,18 +285,12 @@ impl MetaBoard for DefaultMetaBoard {
 
     #[inline]
     fn next_ply(&mut self) {
-        self.player = self.player.opp();
-        if self.player.is_white() {
-            self.turn += 1;
-        }
+        Ply(self.turn, self.player) = self.ply_struct().next();
     }
 
     #[inline]
     fn prev_ply(&mut self) {
-        if self.player.is_white() {
-            self.turn -= 1;
-        }
-        self.player = self.player.opp();
+        Ply(self.turn, self.player) = self.ply_struct().prev();
     }
 
     #[inline]
@@ -271,7 +351,11 @@ impl ChessBoard for DefaultMetaBoard {
     #[inline]
     fn rehash<ZT: ZobristTables>(&self) -> u64 {
         let zobristtable = ZT::static_table();
-        zobristtable.black()
+        let black = match self.player {
+            ChessColor::WHITE => 0,
+            ChessColor::BLACK => zobristtable.black(),
+        };
+        black
             ^ zobristtable.hash_rights(self.trans.rights)
             ^ zobristtable.hash_en_passant(self.trans.en_passant)
     }
@@ -453,8 +537,11 @@ impl ChessBoard for CompactBitBoard {
     /// - Are the echelon masks non-overlapping?
     /// - Are the color masks non-overlapping?
     /// - Are the sum of the echelon masks equal to the sum of the color masks?
+    /// - Does `commoner_at` agree with `piece_at`, see [`assert_commoners_agree`]?
     /// - Is the procedurally updated hash equal to the recomputed hash?
     fn sanity_check<ZT: ZobristTables>(&self) {
+        assert_commoners_agree(self);
+
         for p1 in ChessPiece::VARIANTS {
             for p2 in ChessPiece::VARIANTS {
                 let (p1, p2) = (*p1, *p2);
@@ -473,7 +560,7 @@ impl ChessBoard for CompactBitBoard {
         }
 
         assert_eq!(
-            self.colors[ChessColor::WHITE.ix()] | self.colors[ChessColor::BLACK.ix()],
+            self.colors[ChessColor::WHITE.ix()] & self.colors[ChessColor::BLACK.ix()],
             0,
             "white and black overlap",
         );
@@ -617,8 +704,11 @@ impl ChessBoard for FullBitBoard {
     /// Performs the following checks:
     ///
     /// - All the bit masks are non-overlapping
+    /// - `commoner_at` agrees with `piece_at`, see [`assert_commoners_agree`]
     /// - The procedurally computed hash is equal to the recomputed hash
     fn sanity_check<ZT: ZobristTables>(&self) {
+        assert_commoners_agree(self);
+
         for p1 in ChessPiece::VARIANTS {
             for p2 in ChessPiece::VARIANTS {
                 for c1 in [ChessColor::WHITE, ChessColor::BLACK] {
@@ -762,3 +852,158 @@ impl ChessBoard for FullerBitBoard {
         }
     }
 }
+
+#[test]
+fn man_at_test() {
+    use crate::bitboard::hash::FullZobristTables;
+
+    let board = CompactBitBoard::startpos::<FullZobristTables>();
+    assert_eq!(board.man_at(Square::a1), Some(ChessMan::WHITE_ROOK));
+    assert_eq!(board.man_at(Square::e8), Some(ChessMan::BLACK_KING));
+    assert_eq!(board.man_at(Square::d7), Some(ChessMan::BLACK_PAWN));
+    assert_eq!(board.man_at(Square::e4), None);
+}
+
+#[test]
+fn material_value_test() {
+    use crate::bitboard::{hash::FullZobristTables, setup::parse_position};
+
+    fn recount(board: &impl BitBoard) -> [i32; 2] {
+        let mut res = [0; 2];
+        for ix in 0..64 {
+            if let Some(man) = board.man_at(Square::from_u8(ix))
+                && let Some(ech) = ChessCommoner::from_piece(man.into())
+            {
+                res[ChessColor::from(man).ix()] += MATERIAL[ech.ix()];
+            }
+        }
+        res
+    }
+
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ] {
+        let (compact, _, _) = parse_position::<CompactBitBoard, FullZobristTables>(fen).unwrap();
+        let (full, _, _) = parse_position::<FullBitBoard, FullZobristTables>(fen).unwrap();
+
+        assert_eq!(compact.material_value(), recount(&compact), "{fen}");
+        assert_eq!(full.material_value(), recount(&full), "{fen}");
+    }
+
+    let board = CompactBitBoard::startpos::<FullZobristTables>();
+    assert_eq!(board.material_value(), [3900, 3900]);
+}
+
+#[test]
+fn commoner_at_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, hash::FullZobristTables, movegen::LegalBlessing,
+        moving::clone_make_legal_move, status::random_legal_move, vision::MostlyBits,
+    };
+    use rand::{SeedableRng, rngs::SmallRng};
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+    type ZT = FullZobristTables;
+
+    fn random_games<BB: BitBoard + ChessBoard>() {
+        for seed in 0..10 {
+            let mut rng = SmallRng::seed_from_u64(seed);
+            let mut board = BB::startpos::<ZT>();
+
+            for _ in 0..100 {
+                board.sanity_check::<ZT>();
+                assert_commoners_agree(&board);
+
+                let Some(mv) = random_legal_move::<_, MostlyBits, Legal>(&board, &mut rng) else {
+                    break;
+                };
+                board = clone_make_legal_move::<_, ZT>(&board, mv);
+            }
+        }
+    }
+
+    random_games::<CompactBitBoard>();
+    random_games::<FullBitBoard>();
+    random_games::<FullerBitBoard>();
+
+    let board = CompactBitBoard::startpos::<ZT>();
+    assert_eq!(board.piece_at(Square::e1), Some(ChessPiece::KING));
+    assert_eq!(board.commoner_at(Square::e1), None);
+    assert_eq!(board.commoner_at(Square::d1), Some(ChessCommoner::QUEEN));
+    assert_eq!(board.commoner_at(Square::d4), None);
+}
+
+#[test]
+fn ply_struct_test() {
+    use crate::bitboard::hash::NoHashes;
+
+    let mut board = FullerBitBoard::startpos::<NoHashes>();
+    assert_eq!(board.ply_struct(), Ply(1, ChessColor::WHITE));
+
+    for _ in 0..4 {
+        let before = board.ply_struct();
+        board.next_ply();
+        assert_eq!(before.next(), board.ply_struct());
+        assert_eq!(board.ply_struct(), Ply(board.ply().1, board.ply().0));
+    }
+    assert_eq!(board.ply_struct(), Ply(3, ChessColor::WHITE));
+
+    board.prev_ply();
+    assert_eq!(board.ply_struct(), Ply(2, ChessColor::BLACK));
+    assert_eq!(board.ply_struct().prev(), Ply(2, ChessColor::WHITE));
+}
+
+#[test]
+fn reset_test() {
+    use crate::{
+        bitboard::{hash::FullZobristTables, moving::clone_make_legal_move},
+        notation::fen::FenBoard,
+    };
+
+    type ZT = FullZobristTables;
+
+    let fresh = FullerBitBoard::startpos::<ZT>();
+    let mut board = fresh.clone();
+    for (from, to) in [(Square::e2, Square::e4), (Square::c7, Square::c5)] {
+        let mv = ChessMove::from_squares(&board, from, to, None).unwrap();
+        board = clone_make_legal_move::<_, ZT>(&board, LegalMove(mv));
+    }
+    assert_ne!(board.curr_hash(), fresh.curr_hash());
+
+    board.reset_to_startpos::<ZT>();
+    assert_eq!(
+        FenBoard::from_bitboard(&board).to_string(),
+        FenBoard::from_bitboard(&fresh).to_string()
+    );
+    assert_eq!(board.curr_hash(), fresh.curr_hash());
+    assert_eq!(board.ply(), fresh.ply());
+    assert_eq!(board.trans(), fresh.trans());
+
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    board.reset_to::<ZT>(kiwipete).unwrap();
+    assert_eq!(FenBoard::from_bitboard(&board).to_string(), kiwipete);
+    assert_eq!(board.curr_hash(), board.rehash::<ZT>());
+
+    assert!(board.reset_to::<ZT>("not a fen").is_err());
+    assert_eq!(FenBoard::from_bitboard(&board).to_string(), kiwipete);
+}
+
+#[test]
+fn square_state_test() {
+    use crate::bitboard::hash::NoHashes;
+
+    let board = FullerBitBoard::startpos::<NoHashes>();
+    let white = |sq| board.square_state(sq, ChessColor::WHITE);
+
+    assert_eq!(white(Square::a1), SquareState::Friendly(ChessPiece::ROOK));
+    assert_eq!(white(Square::a8), SquareState::Enemy(ChessPiece::ROOK));
+    assert_eq!(white(Square::e4), SquareState::Empty);
+    assert_eq!(white(Square::e8), SquareState::Enemy(ChessPiece::KING));
+    assert_eq!(
+        board.square_state(Square::e8, ChessColor::BLACK),
+        SquareState::Friendly(ChessPiece::KING)
+    );
+}
@@ -11,12 +11,164 @@ pub struct BitCastling {
 }
 
 impl BitCastling {
+    /// The masks are indexed by [`CastlingDirection`], and hold the squares of both colors.
     pub const STANDARD: BitCastling = BitCastling {
-        rook_move: [0xA000_0000_0000_00A0, 0x0900_0000_0000_0009],
-        king_move: [0x5000_0000_0000_0050, 0x1400_0000_0000_0014],
-        safety: [0x7000_0000_0000_0070, 0x1C00_0000_0000_001C],
-        space: [0x6000_0000_0000_0060, 0x0E00_0000_0000_000E],
+        rook_move: [0x0900_0000_0000_0009, 0xA000_0000_0000_00A0],
+        king_move: [0x1400_0000_0000_0014, 0x5000_0000_0000_0050],
+        safety: [0x1C00_0000_0000_001C, 0x7000_0000_0000_0070],
+        space: [0x0E00_0000_0000_000E, 0x6000_0000_0000_0060],
         back_rank: [0x0000_0000_0000_00FF, 0xFF00_0000_0000_0000],
         rules: CastlingRules::STANDARD,
     };
+
+    /// Derive the castling masks from the start and end squares of the king and rooks.
+    ///
+    /// - The king and rook moves are the start and end squares of each
+    /// - The safety mask is every square the king passes through, including its start and end
+    /// - The space mask is every square the king or rook passes through, except
+    ///   the squares of the king and rook themselves
+    pub const fn from_rules(rules: CastlingRules) -> Self {
+        let mut rook_move = [0; 2];
+        let mut king_move = [0; 2];
+        let mut safety = [0; 2];
+        let mut space = [0; 2];
+        let mut back_rank = [0; 2];
+
+        let mut c = 0;
+        while c < 2 {
+            let king_start = rules.king_start[c];
+            back_rank[c] = 0xFF << (king_start as u8 & 0x38);
+
+            let mut d = 0;
+            while d < 2 {
+                let rook_start = rules.rook_start[c][d];
+                let rook_end = rules.rook_end[c][d];
+                let king_end = rules.king_end[c][d];
+
+                rook_move[d] |= bit(rook_start) ^ bit(rook_end);
+                king_move[d] |= bit(king_start) ^ bit(king_end);
+                safety[d] |= span(king_start, king_end);
+                space[d] |= (span(king_start, king_end) | span(rook_start, rook_end))
+                    & !bit(king_start)
+                    & !bit(rook_start);
+
+                d += 1;
+            }
+            c += 1;
+        }
+
+        let res = BitCastling {
+            rook_move,
+            king_move,
+            safety,
+            space,
+            back_rank,
+            rules,
+        };
+        res.assert_consistent();
+        res
+    }
+
+    /// Panic unless the masks agree with the castling rules:
+    ///
+    /// - The king and rooks start and end on the back rank
+    /// - The king and rook do not end on the same square
+    /// - Every square the king passes through must be safe
+    /// - Every square the king or rook passes through, other than their own, must be empty
+    pub const fn assert_consistent(&self) {
+        let rules = &self.rules;
+
+        let mut c = 0;
+        while c < 2 {
+            let king_start = rules.king_start[c];
+            assert!(
+                bit(king_start) & self.back_rank[c] != 0,
+                "king does not start on the back rank"
+            );
+
+            let mut d = 0;
+            while d < 2 {
+                let rook_start = rules.rook_start[c][d];
+                let rook_end = rules.rook_end[c][d];
+                let king_end = rules.king_end[c][d];
+
+                assert!(
+                    (bit(rook_start) | bit(rook_end) | bit(king_end)) & !self.back_rank[c] == 0,
+                    "castling leaves the back rank"
+                );
+                assert!(
+                    rook_end as u8 != king_end as u8,
+                    "king and rook end on the same square"
+                );
+                assert!(
+                    span(king_start, king_end) & !self.safety[d] == 0,
+                    "king passes through a square not in the safety mask"
+                );
+                assert!(
+                    (span(king_start, king_end) | span(rook_start, rook_end))
+                        & !bit(king_start)
+                        & !bit(rook_start)
+                        & !self.space[d]
+                        == 0,
+                    "king or rook passes through a square not in the space mask"
+                );
+
+                d += 1;
+            }
+            c += 1;
+        }
+    }
+}
+
+#[inline]
+const fn bit(sq: Square) -> u64 {
+    1 << sq as u8
+}
+
+/// All squares from `a` to `b` inclusive, assuming they are on the same rank.
+#[inline]
+const fn span(a: Square, b: Square) -> u64 {
+    let (lo, hi) = if (a as u8) < (b as u8) {
+        (a as u8, b as u8)
+    } else {
+        (b as u8, a as u8)
+    };
+    (!0 >> (63 - hi)) & (!0 << lo)
+}
+
+#[test]
+fn castling_from_rules_test() {
+    let derived = BitCastling::from_rules(CastlingRules::STANDARD);
+    let standard = BitCastling::STANDARD;
+
+    assert_eq!(derived.rook_move, standard.rook_move);
+    assert_eq!(derived.king_move, standard.king_move);
+    assert_eq!(derived.safety, standard.safety);
+    assert_eq!(derived.space, standard.space);
+    assert_eq!(derived.back_rank, standard.back_rank);
+
+    for d in [CastlingDirection::EAST, CastlingDirection::WEST] {
+        assert_ne!(
+            standard.king_move[d.ix()] & (1 << CastlingRules::STANDARD.king_end[0][d.ix()].ix()),
+            0
+        );
+    }
+}
+
+#[test]
+fn castling_consistency_test() {
+    BitCastling::STANDARD.assert_consistent();
+
+    let mut narrow = BitCastling::STANDARD;
+    narrow.safety[CastlingDirection::WEST.ix()] &= !(1 << Square::f1.ix());
+    assert!(std::panic::catch_unwind(|| narrow.assert_consistent()).is_err());
+}
+
+#[test]
+#[should_panic(expected = "castling leaves the back rank")]
+fn castling_inconsistent_rules_test() {
+    BitCastling::from_rules(CastlingRules {
+        rook_end: [[Square::d2, Square::f1], [Square::d8, Square::f8]],
+        ..CastlingRules::STANDARD
+    });
 }
@@ -0,0 +1,214 @@
+//! Compact binary encoding of chess positions.
+//!
+//! Intended for storing large datasets of positions, such as perft
+//! divergence corpora, without pulling in a serialization framework.
+//!
+//! The encoding is a fixed [`ENCODED_LEN`] bytes long:
+//!
+//! - 8 bytes: the occupancy mask, little-endian
+//! - 16 bytes: 4 bits per occupied square in ascending square order,
+//!   low nibble first, holding the chessman on that square
+//! - 1 byte: the active player in bit 0, and the castling rights in bits 1-4
+//!
+//! The chessman codes are 0-5 for white pawn through king, and 6-11 for black
+//! pawn through king. The remaining codes 12 and 13 denote a white or black pawn,
+//! respectively, which can be captured _en passant_.
+//!
+//! Since there are at most 32 chessmen on the board in a legal position, the
+//! encoding does not account for more. The half-move clock and the turn number
+//! are not encoded.
+
+use strum::VariantArray;
+
+use crate::{
+    bitboard::{board::BitBoard, hash::ZobristTables, utils::SliceExtensions},
+    biterate,
+};
+
+use crate::model::*;
+
+/// Length in bytes of an encoded position.
+pub const ENCODED_LEN: usize = 25;
+
+/// Code of a pawn which can be captured _en passant_, offset by color.
+const EN_PASSANT_CODE: u8 = 12;
+
+/// Reasons a byte string cannot be decoded into a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The byte string is not [`ENCODED_LEN`] bytes long.
+    Length(usize),
+    /// The occupancy mask has more than 32 squares set.
+    Occupancy(u32),
+    /// A square holds an unknown chessman code.
+    ChessMan(Square, u8),
+    /// An _en passant_ pawn is misplaced, or there is more than one.
+    EnPassant(Square),
+}
+
+/// Encode a position into the compact binary format.
+///
+/// Panics if there are more than 32 chessmen on the board.
+pub fn encode_position<BB: BitBoard>(board: &BB) -> [u8; ENCODED_LEN] {
+    let mut res = [0; ENCODED_LEN];
+    let total = board.total();
+
+    assert!(
+        total.count_ones() <= 32,
+        "cannot encode more than 32 chessmen"
+    );
+
+    res[0..8].copy_from_slice(&total.to_le_bytes());
+
+    let trans = board.trans();
+    let en_passant = trans.en_passant.map(|ep| ep.capture);
+
+    let mut ix = 0;
+    biterate! {for sq in total; {
+        let color = if board.color(ChessColor::WHITE) & (1 << sq.ix()) != 0 {
+            ChessColor::WHITE
+        } else {
+            ChessColor::BLACK
+        };
+
+        let code = if en_passant == Some(sq) {
+            EN_PASSANT_CODE + color as u8
+        } else {
+            let ech = board.piece_at(sq).unwrap_or(ChessPiece::PAWN);
+            color as u8 * 6 + ech.ix() as u8
+        };
+
+        res[8 + ix / 2] |= code << (4 * (ix % 2));
+        ix += 1;
+    }}
+
+    let (player, _) = board.ply();
+    let mut meta = player as u8;
+    for c in [ChessColor::WHITE, ChessColor::BLACK] {
+        for d in [CastlingDirection::EAST, CastlingDirection::WEST] {
+            if trans.rights[c.ix()][d.ix()] {
+                meta |= 1 << (1 + 2 * c.ix() + d.ix());
+            }
+        }
+    }
+    res[ENCODED_LEN - 1] = meta;
+
+    res
+}
+
+/// Decode a position from the compact binary format.
+///
+/// The resulting board has a half-move clock of 0 and is on turn 1.
+pub fn decode_position<BB: BitBoard, ZT: ZobristTables>(bytes: &[u8]) -> Result<BB, DecodeError> {
+    if bytes.len() != ENCODED_LEN {
+        return Err(DecodeError::Length(bytes.len()));
+    }
+
+    let total = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+
+    if total.count_ones() > 32 {
+        return Err(DecodeError::Occupancy(total.count_ones()));
+    }
+
+    let mut board = BB::empty();
+    let mut en_passant = None;
+
+    let mut ix = 0;
+    biterate! {for sq in total; {
+        let code = (bytes[8 + ix / 2] >> (4 * (ix % 2))) & 0xF;
+        ix += 1;
+
+        let (color, ech) = match code {
+            0..=11 => (
+                if code < 6 { ChessColor::WHITE } else { ChessColor::BLACK },
+                ChessPiece::VARIANTS[code as usize % 6],
+            ),
+            12 | 13 => {
+                let color = if code == 12 { ChessColor::WHITE } else { ChessColor::BLACK };
+                let (rank, square) = match color {
+                    ChessColor::WHITE => (BoardRank::_4, sq as u8 - 8),
+                    ChessColor::BLACK => (BoardRank::_5, sq as u8 + 8),
+                };
+
+                if sq.coords().1 != rank || en_passant.is_some() {
+                    return Err(DecodeError::EnPassant(sq));
+                }
+
+                en_passant = Some(EnPassant {
+                    square: Square::from_u8(square),
+                    capture: sq,
+                });
+
+                (color, ChessPiece::PAWN)
+            }
+            _ => return Err(DecodeError::ChessMan(sq, code)),
+        };
+
+        board.xor(color, ech, 1 << sq.ix());
+    }}
+
+    let meta = bytes[ENCODED_LEN - 1];
+
+    let mut rights = [[false; 2]; 2];
+    for c in [ChessColor::WHITE, ChessColor::BLACK] {
+        for d in [CastlingDirection::EAST, CastlingDirection::WEST] {
+            rights[c.ix()][d.ix()] = meta & (1 << (1 + 2 * c.ix() + d.ix())) != 0;
+        }
+    }
+
+    board.set_transients(Transients {
+        en_passant,
+        halfmove_clock: 0,
+        rights,
+    });
+
+    if meta & 1 != 0 {
+        board.next_ply();
+    }
+
+    board.hash(board.curr_hash() ^ board.rehash::<ZT>());
+
+    Ok(board)
+}
+
+#[test]
+fn encoding_round_trip() {
+    use crate::{
+        bitboard::{
+            board::{CompactBitBoard, MetaBoard},
+            hash::FullZobristTables,
+        },
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "8/8/8/8/8/8/8/k6K b - - 0 1",
+    ];
+
+    for fen in fens {
+        let board = FenBoard::parser()
+            .parse(fen)
+            .into_result()
+            .unwrap()
+            .as_bitboard::<CompactBitBoard, FullZobristTables>();
+
+        let bytes = encode_position(&board);
+        let decoded = decode_position::<CompactBitBoard, FullZobristTables>(&bytes).expect(fen);
+
+        assert_eq!(decoded, board, "{fen}");
+        assert_eq!(decoded.curr_hash(), board.curr_hash(), "{fen}");
+        assert_eq!(encode_position(&decoded), bytes, "{fen}");
+    }
+
+    assert_eq!(
+        decode_position::<CompactBitBoard, FullZobristTables>(&[0; 3]),
+        Err(DecodeError::Length(3))
+    );
+}
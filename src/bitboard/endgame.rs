@@ -0,0 +1,273 @@
+//! Endgame knowledge.
+//!
+//! Full endgame tablebases are out of scope for this library, but
+//! some endgames are simple enough that they can be solved exactly
+//! by a small exhaustive search.
+
+use crate::bitboard::{
+    attacking::attacks,
+    board::BitBoard,
+    hash::NoHashes,
+    movegen::{BlessingStrategy, enumerate},
+    moving::clone_make_legal_move,
+    utils::{FILES, biterate},
+    vision::Panopticon,
+};
+
+use crate::model::*;
+
+/// Find a forced checkmate in the trivially won endgames, king and queen
+/// versus king and king and rook versus king.
+///
+/// The side to move must be the side with the queen or rook. Returns the
+/// first move of the shortest forced mate along with the distance to mate
+/// in moves of the winning side, or `None` if the position is not one of these
+/// endgames or no mate exists within `max_depth` moves.
+///
+/// The search is an exhaustive minimax, and so `max_depth` should be kept small.
+pub fn solve_trivial_mate<BB, X, L>(board: &BB, max_depth: usize) -> Option<(ChessMove, usize)>
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    if !is_trivial_mate_material(board, board.ply().0) {
+        return None;
+    }
+
+    for depth in 1..=max_depth {
+        if let Some(mv) = mating_move::<BB, X, L>(board, depth) {
+            return Some((mv, depth));
+        }
+    }
+
+    None
+}
+
+/// Does `player` have a lone king and a queen or rook against a lone king?
+fn is_trivial_mate_material<BB: BitBoard>(board: &BB, player: ChessColor) -> bool {
+    let heavy = board.men(player, ChessPiece::QUEEN) | board.men(player, ChessPiece::ROOK);
+
+    heavy.count_ones() == 1
+        && board.color(player) == board.men(player, ChessPiece::KING) | heavy
+        && is_bare_king(board, player.opp())
+}
+
+/// Does `color` have nothing left but its king?
+pub fn is_bare_king<BB: BitBoard>(board: &BB, color: ChessColor) -> bool {
+    board.color(color) == board.men(color, ChessPiece::KING)
+}
+
+/// Find a move for the active player that forces mate within `depth` moves.
+fn mating_move<BB, X, L>(board: &BB, depth: usize) -> Option<ChessMove>
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    let mut moves = vec![];
+    enumerate::<BB, X, L>(board, &mut moves);
+
+    moves
+        .into_iter()
+        .find(|mv| {
+            let board = clone_make_legal_move::<BB, NoHashes>(board, *mv);
+            is_forced_mate::<BB, X, L>(&board, depth - 1)
+        })
+        .map(|mv| mv.0)
+}
+
+/// Is the active player checkmated, or will be within `depth` moves of the opponent
+/// no matter what they do?
+fn is_forced_mate<BB, X, L>(board: &BB, depth: usize) -> bool
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    let mut replies = vec![];
+    enumerate::<BB, X, L>(board, &mut replies);
+
+    if replies.is_empty() {
+        return attacks::<BB, X>(board, board.ply().0.opp()).check();
+    }
+
+    if depth == 0 {
+        return false;
+    }
+
+    replies.into_iter().all(|mv| {
+        mv.0.capture.is_none() && {
+            let board = clone_make_legal_move::<BB, NoHashes>(board, mv);
+            mating_move::<BB, X, L>(&board, depth).is_some()
+        }
+    })
+}
+
+/// The pawns of `color` with no enemy pawns ahead of them on the same or adjacent files.
+pub fn passed_pawns<BB: BitBoard>(board: &BB, color: ChessColor) -> u64 {
+    let enemy = board.men(color.opp(), ChessPiece::PAWN);
+    let mut res = 0;
+    biterate! {for sq in board.men(color, ChessPiece::PAWN); {
+        if front_span(sq, color) & enemy == 0 {
+            res |= 1 << sq.ix();
+        }
+    }}
+    res
+}
+
+/// The squares ahead of a pawn on its own and the adjacent files.
+fn front_span(sq: Square, color: ChessColor) -> u64 {
+    let (file, rank) = (sq.ix() % 8, sq.ix() / 8);
+
+    let own = FILES[file];
+    let files = own | (own << 1 & !FILES[0]) | (own >> 1 & !FILES[7]);
+    let ahead = match color {
+        ChessColor::WHITE => (!0u64).checked_shl((rank as u32 + 1) * 8).unwrap_or(0),
+        ChessColor::BLACK => (1u64 << (rank * 8)) - 1,
+    };
+
+    files & ahead
+}
+
+/// Find a passed pawn of `color` which the enemy king cannot catch
+/// before it promotes, by the rule of the square.
+///
+/// Only applies when the opponent has nothing but king and pawns, and the
+/// path of the pawn is clear. The side to move is accounted for, and so is
+/// the double step of a pawn on its starting rank.
+pub fn unstoppable_pawn<BB: BitBoard>(board: &BB, color: ChessColor) -> Option<Square> {
+    let defender = color.opp();
+    let king = board.men(defender, ChessPiece::KING);
+    if king == 0 || board.color(defender) != king | board.men(defender, ChessPiece::PAWN) {
+        return None;
+    }
+
+    let king = Square::from_u8(king.trailing_zeros() as u8);
+    let (king_file, king_rank) = (king.ix() % 8, king.ix() / 8);
+    let tempo = (board.ply().0 == defender) as usize;
+    let (promotion_rank, start_rank) = match color {
+        ChessColor::WHITE => (7, 1),
+        ChessColor::BLACK => (0, 6),
+    };
+
+    let mut res = None;
+    biterate! {for sq in passed_pawns(board, color); {
+        let (file, rank) = (sq.ix() % 8, sq.ix() / 8);
+        let path = front_span(sq, color) & FILES[file];
+
+        let pawn_distance = rank.abs_diff(promotion_rank) - (rank == start_rank) as usize;
+        let king_distance = king_file.abs_diff(file).max(king_rank.abs_diff(promotion_rank));
+
+        if res.is_none() && path & board.total() == 0 && king_distance > pawn_distance + tempo {
+            res = Some(sq);
+        }
+    }}
+    res
+}
+
+#[test]
+fn unstoppable_pawn_test() {
+    use crate::bitboard::{board::FullerBitBoard, hash::FullZobristTables, setup::parse_position};
+
+    let unstoppable = |fen| {
+        let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+        unstoppable_pawn(&board, ChessColor::WHITE)
+    };
+
+    // The king is four moves from e8, the pawn three
+    assert_eq!(
+        unstoppable("k7/8/8/4P3/8/8/8/7K w - - 0 1"),
+        Some(Square::e5)
+    );
+    // With the move, the king reaches d7 in time
+    assert_eq!(unstoppable("k7/8/8/4P3/8/8/8/7K b - - 0 1"), None);
+    assert_eq!(unstoppable("3k4/8/8/4P3/8/8/8/7K w - - 0 1"), None);
+
+    // The double step makes it five moves from a2 to a8
+    assert_eq!(
+        unstoppable("8/8/8/8/6k1/8/P7/7K w - - 0 1"),
+        Some(Square::a2)
+    );
+    assert_eq!(unstoppable("8/8/8/8/5k2/8/P7/7K w - - 0 1"), None);
+
+    assert_eq!(
+        unstoppable("k7/4p3/8/4P3/8/8/8/7K w - - 0 1"),
+        None,
+        "blocked"
+    );
+    assert_eq!(
+        unstoppable("k7/3p4/8/4P3/8/8/8/7K w - - 0 1"),
+        None,
+        "not passed"
+    );
+    assert_eq!(
+        unstoppable("kn6/8/8/4P3/8/8/8/7K w - - 0 1"),
+        None,
+        "knight"
+    );
+}
+
+#[test]
+fn bare_king_test() {
+    use crate::bitboard::{board::FullerBitBoard, hash::FullZobristTables, setup::parse_position};
+
+    let (board, _, _) =
+        parse_position::<FullerBitBoard, FullZobristTables>("8/8/3k4/8/8/8/3QK3/8 w - - 0 1")
+            .unwrap();
+    assert!(!is_bare_king(&board, ChessColor::WHITE));
+    assert!(is_bare_king(&board, ChessColor::BLACK));
+
+    let (board, _, _) =
+        parse_position::<FullerBitBoard, FullZobristTables>("8/8/3kq3/8/8/8/4K3/8 b - - 0 1")
+            .unwrap();
+    assert!(is_bare_king(&board, ChessColor::WHITE));
+    assert!(!is_bare_king(&board, ChessColor::BLACK));
+}
+
+#[test]
+fn trivial_mate_test() {
+    use crate::{
+        bitboard::{
+            attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+            movegen::LegalBlessing, vision::MostlyBits,
+        },
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    fn solve(fen: &str, max_depth: usize) -> Option<(ChessMove, usize)> {
+        let board = FenBoard::parser()
+            .parse(fen)
+            .into_result()
+            .unwrap()
+            .as_bitboard::<FullerBitBoard, FullZobristTables>();
+
+        solve_trivial_mate::<_, MostlyBits, LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>>(
+            &board, max_depth,
+        )
+    }
+
+    // Qh8# and Qb7# both mate immediately
+    let (mv, dtm) = solve("k7/8/1K6/8/8/8/8/7Q w - - 0 1", 3).unwrap();
+    assert_eq!(dtm, 1);
+    assert!([Square::h8, Square::b7].contains(&mv.to));
+
+    // Rb7 stalemates, mate takes Kc7 Ka7 Ra1#
+    let (_, dtm) = solve("k7/8/2K5/8/8/8/8/1R6 w - - 0 1", 3).unwrap();
+    assert_eq!(dtm, 2);
+
+    assert_eq!(solve("k7/8/2K5/8/8/8/8/1R6 w - - 0 1", 1), None);
+    assert_eq!(
+        solve("k7/8/1K6/8/8/8/8/6BQ w - - 0 1", 3),
+        None,
+        "extra material"
+    );
+    assert_eq!(
+        solve(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            3
+        ),
+        None
+    );
+}
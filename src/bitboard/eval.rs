@@ -0,0 +1,443 @@
+//! Evaluation terms.
+//!
+//! Static features of a chess position which are useful as
+//! components of an evaluation function. These are cheaply derived from
+//! the vision primitives, and do not account for legality.
+
+use std::cmp::Reverse;
+
+use strum::VariantArray;
+
+use crate::{
+    bitboard::{
+        attacking::{attackers, attackers_within, checkers},
+        board::BitBoard,
+        hash::NoHashes,
+        movegen::{BlessingStrategy, enumerate},
+        moving::clone_make_legal_move,
+        utils::{FILES, RANKS, SliceExtensions},
+        vision::{Panopticon, PieceVision, Vision},
+    },
+    biterate,
+};
+
+use crate::model::*;
+
+/// Conventional material values of the echelons in centipawns, indexed by [`ChessPiece::ix`].
+///
+/// The king is given a value greater than all other material combined.
+pub const MATERIAL: [i32; 6] = [100, 300, 300, 500, 900, 10_000];
+
+/// Static exchange evaluation of a move: the material balance for the moving
+/// player of the sequence of captures on the destination square, where each side
+/// recaptures with its least valuable attacker and may stop whenever it is ahead.
+///
+/// Pins and checks are not taken into account.
+pub fn see<BB: BitBoard, X: Panopticon>(board: &BB, mv: ChessMove) -> i32 {
+    let mut occupancy = board.total() & !(1 << mv.from.ix());
+    let mut gain = vec![mv.capture.map_or(0, |c| MATERIAL[ChessPiece::from(c).ix()])];
+    let mut on_square = mv.ech;
+    let mut side = board.ply().0.opp();
+
+    loop {
+        let atks = attackers_within::<BB, X>(board, mv.to, side, occupancy);
+        let Some((ech, lva)) = least_valuable(board, side, atks) else {
+            break;
+        };
+
+        gain.push(MATERIAL[on_square.ix()] - gain[gain.len() - 1]);
+        occupancy &= !lva;
+        on_square = ech;
+        side = side.opp();
+    }
+
+    while gain.len() > 1 {
+        let last = gain.pop().unwrap();
+        let prev = gain.len() - 1;
+        gain[prev] = -(-gain[prev]).max(last);
+    }
+
+    gain[0]
+}
+
+/// Find the least valuable of the chessmen of `color` within `mask`.
+fn least_valuable<BB: BitBoard>(
+    board: &BB,
+    color: ChessColor,
+    mask: u64,
+) -> Option<(ChessPiece, u64)> {
+    ChessPiece::VARIANTS.clones().find_map(|ech| {
+        let men = board.men(color, ech) & mask;
+        (men != 0).then(|| (ech, men & men.wrapping_neg()))
+    })
+}
+
+/// Order the legal moves by a cheap one-ply heuristic, best first:
+/// the static exchange evaluation of captures, plus small bonuses for
+/// giving check and for moving to the center.
+pub fn ranked_moves<BB, X, L>(board: &BB) -> Vec<(LegalMove, i32)>
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    const CHECK_BONUS: i32 = 50;
+    const CENTER_BONUS: i32 = 10;
+    const CENTER: u64 = 0x0000_0018_1800_0000;
+
+    let player = board.ply().0;
+    let mut moves = vec![];
+    enumerate::<BB, X, L>(board, &mut moves);
+
+    let mut res: Vec<_> = moves
+        .into_iter()
+        .map(|mv| {
+            let mut score = if mv.0.capture.is_some() {
+                see::<BB, X>(board, mv.0)
+            } else {
+                0
+            };
+
+            let after = clone_make_legal_move::<BB, NoHashes>(board, mv);
+            if checkers::<BB, X>(&after, player.opp()) != 0 {
+                score += CHECK_BONUS;
+            }
+
+            if CENTER & (1 << mv.0.to.ix()) != 0 {
+                score += CENTER_BONUS;
+            }
+
+            (mv, score)
+        })
+        .collect();
+
+    res.sort_by_key(|(_, score)| Reverse(*score));
+    res
+}
+
+/// Count the pseudo-legal destination squares of the knights, bishops,
+/// rooks, and queens of one color.
+pub fn mobility<BB: BitBoard, X: Panopticon>(board: &BB, color: ChessColor) -> i32 {
+    mobility_excluding::<BB, X>(board, color, 0)
+}
+
+/// Like [`mobility`], but also excluding the squares attacked by enemy pawns,
+/// as moving there would usually lose the piece.
+pub fn safe_mobility<BB: BitBoard, X: Panopticon>(board: &BB, color: ChessColor) -> i32 {
+    let pan = X::new(board.total());
+    let pawns = board.men(color.opp(), ChessPiece::PAWN);
+    let pawn_attacks = match color.opp() {
+        ChessColor::WHITE => pan.white_pawn().surveil(pawns),
+        ChessColor::BLACK => pan.black_pawn().surveil(pawns),
+    };
+    mobility_excluding::<BB, X>(board, color, pawn_attacks)
+}
+
+/// Count the chessmen of each color, white first, which attack a square.
+///
+/// Only direct attacks are counted, not those through other chessmen.
+pub fn square_control<BB: BitBoard, X: Panopticon>(board: &BB, sq: Square) -> (u32, u32) {
+    (
+        attackers::<BB, X>(board, sq, ChessColor::WHITE).count_ones(),
+        attackers::<BB, X>(board, sq, ChessColor::BLACK).count_ones(),
+    )
+}
+
+/// Sum over all squares of the difference in the number of white and black
+/// attackers, weighted by the importance of the square, see [`square_weight`].
+///
+/// Positive when white controls more of the board, and zero for symmetric positions.
+pub fn net_control<BB: BitBoard, X: Panopticon>(board: &BB) -> i32 {
+    (0..64)
+        .map(Square::from_u8)
+        .map(|sq| {
+            let (white, black) = square_control::<BB, X>(board, sq);
+            (white as i32 - black as i32) * square_weight(sq)
+        })
+        .sum()
+}
+
+/// Importance of a square, from 1 on the edge of the board to 4 in the center.
+pub fn square_weight(sq: Square) -> i32 {
+    let (file, rank) = (sq.ix() % 8, sq.ix() / 8);
+    1 + file.min(7 - file).min(rank.min(7 - rank)) as i32
+}
+
+/// Material imbalance features, indexed by [`ChessColor::ix`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Imbalance {
+    /// Having bishops on both light and dark squares.
+    pub bishop_pair: [bool; 2],
+    /// Having two or more rooks.
+    pub rook_pair: [bool; 2],
+    pub knights: [u32; 2],
+    pub bishops: [u32; 2],
+}
+
+/// Compute the material imbalance features of both colors.
+pub fn imbalance<BB: BitBoard>(board: &BB) -> Imbalance {
+    const LIGHT_SQUARES: u64 = 0x55AA_55AA_55AA_55AA;
+
+    let mut res = Imbalance {
+        bishop_pair: [false; 2],
+        rook_pair: [false; 2],
+        knights: [0; 2],
+        bishops: [0; 2],
+    };
+
+    for color in [ChessColor::WHITE, ChessColor::BLACK] {
+        let bishops = board.men(color, ChessPiece::BISHOP);
+        res.bishop_pair[color.ix()] = bishops & LIGHT_SQUARES != 0 && bishops & !LIGHT_SQUARES != 0;
+        res.rook_pair[color.ix()] = board.men(color, ChessPiece::ROOK).count_ones() >= 2;
+        res.knights[color.ix()] = board.men(color, ChessPiece::KNIGHT).count_ones();
+        res.bishops[color.ix()] = bishops.count_ones();
+    }
+
+    res
+}
+
+/// The files without any pawns, as a mask of whole files.
+pub fn open_files(board: &impl BitBoard) -> u64 {
+    !file_fill(
+        board.men(ChessColor::WHITE, ChessPiece::PAWN)
+            | board.men(ChessColor::BLACK, ChessPiece::PAWN),
+    )
+}
+
+/// The files without any pawns of `color`, as a mask of whole files.
+///
+/// This includes the open files, see [`open_files`].
+pub fn semi_open_files(board: &impl BitBoard, color: ChessColor) -> u64 {
+    !file_fill(board.men(color, ChessPiece::PAWN))
+}
+
+/// The whole files of the squares in a mask.
+fn file_fill(mask: u64) -> u64 {
+    let mut res = mask;
+    res |= res >> 8;
+    res |= res >> 16;
+    res |= res >> 32;
+    (res & RANKS[0]) * FILES[0]
+}
+
+fn mobility_excluding<BB: BitBoard, X: Panopticon>(
+    board: &BB,
+    color: ChessColor,
+    excluded: u64,
+) -> i32 {
+    let pan = X::new(board.total());
+    let blocked = board.color(color) | excluded;
+
+    piece_mobility(pan.knight(), board.men(color, ChessPiece::KNIGHT), blocked)
+        + piece_mobility(pan.bishop(), board.men(color, ChessPiece::BISHOP), blocked)
+        + piece_mobility(pan.rook(), board.men(color, ChessPiece::ROOK), blocked)
+        + piece_mobility(pan.queen(), board.men(color, ChessPiece::QUEEN), blocked)
+}
+
+fn piece_mobility<P: PieceVision>(piece: P, pieces: u64, blocked: u64) -> i32 {
+    let mut res = 0;
+    biterate! {for sq in pieces; {
+        res += piece.hits(sq, blocked).count_ones() as i32;
+    }}
+    res
+}
+
+#[test]
+fn imbalance_test() {
+    use crate::{
+        bitboard::{board::FullerBitBoard, hash::FullZobristTables},
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    let board = FenBoard::parser()
+        .parse("r3k1nr/8/8/2b5/8/8/8/RNB1KB2 w - - 0 1")
+        .into_result()
+        .unwrap()
+        .as_bitboard::<FullerBitBoard, FullZobristTables>();
+
+    let imb = imbalance(&board);
+    let (w, b) = (ChessColor::WHITE.ix(), ChessColor::BLACK.ix());
+
+    assert!(imb.bishop_pair[w]);
+    assert!(!imb.bishop_pair[b]);
+    assert!(!imb.rook_pair[w]);
+    assert!(imb.rook_pair[b]);
+    assert_eq!((imb.knights[w], imb.bishops[w]), (1, 2));
+    assert_eq!((imb.knights[b], imb.bishops[b]), (1, 1));
+}
+
+#[test]
+fn open_files_test() {
+    use crate::bitboard::{
+        board::FullerBitBoard, hash::FullZobristTables, setup::parse_position, utils::file_mask,
+    };
+
+    let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(
+        "r1bqk2r/pppp1ppp/2n2n2/2b5/2B5/2N2N2/PPP2PPP/R1BQ1RK1 w kq - 0 1",
+    )
+    .unwrap();
+
+    let e_file = file_mask(BoardFile::e_);
+    let d_file = file_mask(BoardFile::d_);
+    assert_eq!(open_files(&board), e_file);
+    assert_eq!(semi_open_files(&board, ChessColor::WHITE), d_file | e_file);
+    assert_eq!(semi_open_files(&board, ChessColor::BLACK), e_file);
+
+    let (board, _, _) =
+        parse_position::<FullerBitBoard, FullZobristTables>("4k3/8/8/8/8/8/8/4K3 w - - 0 1")
+            .unwrap();
+    assert_eq!(open_files(&board), !0);
+}
+
+#[test]
+fn mobility_test() {
+    use crate::{
+        bitboard::{board::FullerBitBoard, hash::FullZobristTables, vision::MostlyBits},
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    let board = FenBoard::parser()
+        .parse("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+        .into_result()
+        .unwrap()
+        .as_bitboard::<FullerBitBoard, FullZobristTables>();
+
+    let white = mobility::<_, MostlyBits>(&board, ChessColor::WHITE);
+    let black = mobility::<_, MostlyBits>(&board, ChessColor::BLACK);
+    assert_eq!(white, 4);
+    assert_eq!(white, black);
+
+    let board = FenBoard::parser()
+        .parse("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+        .into_result()
+        .unwrap()
+        .as_bitboard::<FullerBitBoard, FullZobristTables>();
+
+    assert_eq!(mobility::<_, MostlyBits>(&board, ChessColor::WHITE), 14);
+    assert_eq!(
+        safe_mobility::<_, MostlyBits>(&board, ChessColor::WHITE),
+        13
+    );
+}
+
+#[test]
+fn square_control_test() {
+    use crate::{
+        bitboard::{board::FullerBitBoard, hash::FullZobristTables, vision::MostlyBits},
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    // After 1.e4 e5 2.Nf3 Nc6 3.d4, d4 is attacked by the e-pawn and c-knight,
+    // and defended by the f-knight and queen
+    let board = FenBoard::parser()
+        .parse("r1bqkbnr/pppp1ppp/2n5/4p3/3PP3/5N2/PPP2PPP/RNBQKB1R b KQkq d3 0 3")
+        .into_result()
+        .unwrap()
+        .as_bitboard::<FullerBitBoard, FullZobristTables>();
+
+    assert_eq!(square_control::<_, MostlyBits>(&board, Square::d4), (2, 2));
+    assert_eq!(square_control::<_, MostlyBits>(&board, Square::e5), (2, 1));
+    assert_eq!(square_control::<_, MostlyBits>(&board, Square::g5), (2, 1));
+    assert_eq!(square_control::<_, MostlyBits>(&board, Square::a4), (0, 0));
+}
+
+#[test]
+fn net_control_test() {
+    use crate::bitboard::{
+        board::FullerBitBoard, hash::FullZobristTables, setup::parse_position, vision::MostlyBits,
+    };
+
+    let control = |fen| {
+        let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+        net_control::<_, MostlyBits>(&board)
+    };
+
+    assert_eq!(square_weight(Square::a1), 1);
+    assert_eq!(square_weight(Square::b7), 2);
+    assert_eq!(square_weight(Square::e4), 4);
+
+    assert_eq!(
+        control("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+        0
+    );
+
+    let lopsided = control("4k3/8/8/8/3Q4/2N5/8/4K3 w - - 0 1");
+    assert!(lopsided > 0);
+    assert_eq!(control("4k3/8/2n5/3q4/8/8/8/4K3 w - - 0 1"), -lopsided);
+}
+
+#[test]
+fn see_test() {
+    use crate::{
+        bitboard::{board::FullerBitBoard, hash::FullZobristTables, vision::MostlyBits},
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    let board = |fen: &str| {
+        FenBoard::parser()
+            .parse(fen)
+            .into_result()
+            .unwrap()
+            .as_bitboard::<FullerBitBoard, FullZobristTables>()
+    };
+
+    let rxe5 = ChessMove {
+        ech: ChessPiece::ROOK,
+        from: Square::e1,
+        to: Square::e5,
+        special: None,
+        capture: Some(ChessCommoner::PAWN),
+    };
+
+    // Undefended pawn
+    assert_eq!(
+        see::<_, MostlyBits>(&board("4k3/8/8/4p3/8/8/8/4RK2 w - - 0 1"), rxe5),
+        100
+    );
+    // Defended by a pawn, losing the exchange
+    assert_eq!(
+        see::<_, MostlyBits>(&board("4k3/8/3p4/4p3/8/8/8/4RK2 w - - 0 1"), rxe5),
+        -400
+    );
+    // Defended by a rook, but the second white rook behind the first wins the pawn
+    assert_eq!(
+        see::<_, MostlyBits>(
+            &board("4r1k1/8/8/4p3/8/8/4R3/4RK2 w - - 0 1"),
+            ChessMove {
+                from: Square::e2,
+                ..rxe5
+            }
+        ),
+        100
+    );
+}
+
+#[test]
+fn ranked_moves_test() {
+    use crate::{
+        bitboard::{
+            attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+            movegen::LegalBlessing, vision::MostlyBits,
+        },
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    let board = FenBoard::parser()
+        .parse("4k3/pp6/8/3q4/8/2N5/PP6/4K3 w - - 0 1")
+        .into_result()
+        .unwrap()
+        .as_bitboard::<FullerBitBoard, FullZobristTables>();
+
+    let ranked =
+        ranked_moves::<_, MostlyBits, LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>>(&board);
+
+    let (best, score) = ranked[0];
+    assert_eq!((best.0.from, best.0.to), (Square::c3, Square::d5));
+    assert_eq!(score, 900 + 10);
+    assert!(ranked.is_sorted_by_key(|(_, score)| Reverse(*score)));
+}
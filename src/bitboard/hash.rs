@@ -126,7 +126,9 @@ impl ZobristDetails for DefaultZobristDetails {
         let mut res = 0;
         for c in [ChessColor::WHITE, ChessColor::BLACK] {
             for d in [CastlingDirection::EAST, CastlingDirection::WEST] {
-                res ^= self.rights[c.ix()][d.ix()];
+                if rights[c.ix()][d.ix()] {
+                    res ^= self.rights[c.ix()][d.ix()];
+                }
             }
         }
         res
@@ -193,6 +195,30 @@ pub trait ZobristTables: ZobristDetails + 'static {
     fn hash_castling(&self, player: ChessColor, king_bits: u64, rook_bits: u64) -> u64;
 }
 
+/// The Zobrist hash of a position held in a mailbox representation,
+/// equal to the hash reported by a bitboard of the same position.
+pub fn hash_data_board<ZT: ZobristTables>(
+    board: &DataBoard<Option<ChessMan>>,
+    to_move: ChessColor,
+    trans: Transients,
+) -> u64 {
+    let zobristtable = ZT::static_table();
+
+    let mut res =
+        zobristtable.hash_rights(trans.rights) ^ zobristtable.hash_en_passant(trans.en_passant);
+    if to_move == ChessColor::BLACK {
+        res ^= zobristtable.black();
+    }
+
+    for (ix, man) in board.0.iter().enumerate() {
+        if let &Some(man) = man {
+            res ^= zobristtable.hash_square(man.into(), man.into(), Square::from_u8(ix as u8));
+        }
+    }
+
+    res
+}
+
 /// Compact Zobrist hashing tables.
 ///
 /// An implementation of zobrist hashing using less
@@ -478,3 +504,32 @@ impl BuildHasher for ZobHasher {
         ZobHasher(0)
     }
 }
+
+#[test]
+fn hash_data_board_test() {
+    use crate::bitboard::{
+        board::{BitBoard, CompactBitBoard, FullerBitBoard},
+        setup::parse_position,
+    };
+
+    fn check<BB: BitBoard, ZT: ZobristTables>(fen: &str) {
+        let (board, _, _) = parse_position::<BB, ZT>(fen).unwrap();
+        let data = DataBoard::from_bitboard(&board);
+        assert_eq!(
+            hash_data_board::<ZT>(&data, board.ply().0, board.trans()),
+            board.curr_hash(),
+            "{fen}"
+        );
+    }
+
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w Kq - 3 7",
+        "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2",
+    ] {
+        check::<CompactBitBoard, CompactZobristTables>(fen);
+        check::<CompactBitBoard, FullZobristTables>(fen);
+        check::<FullerBitBoard, FullZobristTables>(fen);
+    }
+}
@@ -10,10 +10,19 @@ pub mod attacking;
 pub mod binary;
 pub mod board;
 pub mod castling;
+pub mod encoding;
+pub mod endgame;
+pub mod eval;
 pub mod hash;
 pub mod movegen;
 pub mod moving;
 pub mod perft;
+pub mod polyglot;
+pub mod san;
+pub mod search;
 pub mod setup;
+pub mod status;
+pub mod suite;
 pub mod utils;
+pub mod variant;
 pub mod vision;
@@ -1,14 +1,20 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, mem::MaybeUninit};
 
 use crate::bitboard::{
-    attacking::{AttackMaskGenerator, AttackMaskStrategy, Attacks},
+    attacking::{
+        AttackMaskGenerator, AttackMaskStrategy, Attacks, CachedAttackStrategy, attacks, checkers,
+        king_in_check,
+    },
     board::BitBoard,
     castling,
-    utils::biterate,
+    hash::NoHashes,
+    moving::{clone_make_legal_move, clone_make_pseudolegal_move},
+    utils::{biterate, rank_mask},
     vision::{Panopticon, PawnVision, PieceVision, Vision},
 };
 
 use crate::model::*;
+use crate::notation::fen::FenBoard;
 
 pub trait BlessingStrategy {
     type Blessing;
@@ -26,13 +32,94 @@ pub trait MoveBlesser<'a, BB: BitBoard> {
     fn bless(&self, board: &'a BB, mv: ChessMove) -> Option<Self::BlessedMove>;
 
     #[inline]
-    fn bless_into(&self, board: &'a BB, mv: ChessMove, buffer: &mut Vec<Self::BlessedMove>) {
+    fn bless_into(
+        &self,
+        board: &'a BB,
+        mv: ChessMove,
+        buffer: &mut impl MoveBuffer<Self::BlessedMove>,
+    ) {
         if let Some(b) = self.bless(board, mv) {
             buffer.push(b)
         }
     }
 }
 
+/// A buffer which enumerated moves are pushed into.
+pub trait MoveBuffer<T> {
+    fn clear(&mut self);
+    fn push(&mut self, mv: T);
+}
+
+impl<T> MoveBuffer<T> for Vec<T> {
+    #[inline]
+    fn clear(&mut self) {
+        Vec::clear(self)
+    }
+
+    #[inline]
+    fn push(&mut self, mv: T) {
+        Vec::push(self, mv)
+    }
+}
+
+/// The greatest number of moves a move list can hold, which is more than the
+/// at most 218 legal moves of any reachable chess position.
+pub const MAX_MOVES: usize = 256;
+
+/// A move list of fixed capacity, for enumerating moves without allocating.
+pub struct MoveList {
+    moves: [MaybeUninit<LegalMove>; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        Self {
+            moves: [MaybeUninit::uninit(); MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_slice(&self) -> &[LegalMove] {
+        // SAFETY: the first `len` moves have been initialized by `push`
+        unsafe { std::slice::from_raw_parts(self.moves.as_ptr().cast(), self.len) }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, LegalMove> {
+        self.as_slice().iter()
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MoveBuffer<LegalMove> for MoveList {
+    #[inline]
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// # Panics
+    /// If the list already holds [`MAX_MOVES`] moves.
+    #[inline]
+    fn push(&mut self, mv: LegalMove) {
+        assert!(self.len < MAX_MOVES, "move list is full");
+        self.moves[self.len] = MaybeUninit::new(mv);
+        self.len += 1;
+    }
+}
+
 pub struct NoBlessing;
 
 #[repr(transparent)]
@@ -76,7 +163,7 @@ impl<'a, BB: BitBoard, AS: AttackMaskStrategy> MoveBlesser<'a, BB>
 
     fn new(board: &'a BB) -> Self {
         let attack_strat = AS::new(board);
-        let cached_attack = attack_strat.attacks(board, board.ply().0).attack;
+        let cached_attack = attack_strat.attacks(board, board.ply().0.opp()).attack;
         LegalMoveBlesser {
             attack_strat,
             cached_attack,
@@ -85,7 +172,7 @@ impl<'a, BB: BitBoard, AS: AttackMaskStrategy> MoveBlesser<'a, BB>
 
     fn bless(&self, board: &'a BB, mv: ChessMove) -> Option<Self::BlessedMove> {
         let player = board.ply().0;
-        if self.attack_strat.attacks_after(board, player, mv).check() {
+        if self.attack_strat.check_after(board, player.opp(), mv) {
             return None;
         } else if let Some(ix) = CastlingDirection::from_special(mv.special) {
             let castling = board.castling();
@@ -102,81 +189,538 @@ impl<'a, BB: BitBoard, AS: AttackMaskStrategy> MoveBlesser<'a, BB>
     }
 }
 
+/// Legal move blessing strategy which also reports whether each move gives check,
+/// for move ordering and annotation.
+pub struct CheckAwareBlessing<AS: AttackMaskStrategy>(PhantomData<AS>);
+
+pub struct CheckAwareMoveBlesser<'a, BB: BitBoard + 'a, AS: AttackMaskStrategy> {
+    legal: LegalMoveBlesser<'a, BB, AS>,
+}
+
+impl<AS: AttackMaskStrategy> BlessingStrategy for CheckAwareBlessing<AS> {
+    type Blessing = (LegalMove, bool);
+    type Blesser<'a, BB: BitBoard + 'a> = CheckAwareMoveBlesser<'a, BB, AS>;
+}
+
+impl<'a, BB: BitBoard, AS: AttackMaskStrategy> MoveBlesser<'a, BB>
+    for CheckAwareMoveBlesser<'a, BB, AS>
+{
+    type BlessedMove = (LegalMove, bool);
+
+    fn new(board: &'a BB) -> Self {
+        CheckAwareMoveBlesser {
+            legal: LegalMoveBlesser::new(board),
+        }
+    }
+
+    fn bless(&self, board: &'a BB, mv: ChessMove) -> Option<Self::BlessedMove> {
+        let legal = self.legal.bless(board, mv)?;
+        let check = self
+            .legal
+            .attack_strat
+            .check_after(board, board.ply().0, mv);
+        Some((legal, check))
+    }
+}
+
+/// Blessing strategy which runs two legal move blessing strategies side by side,
+/// panicking if they ever disagree on whether a move is legal.
+///
+/// Intended for validating an optimized strategy `C` against a reference
+/// strategy `R` in tests and while debugging.
+pub struct DebugLegalBlessing<R: BlessingStrategy, C: BlessingStrategy>(PhantomData<(R, C)>);
+
+pub struct DebugLegalMoveBlesser<'a, BB: BitBoard + 'a, R: BlessingStrategy, C: BlessingStrategy> {
+    reference: R::Blesser<'a, BB>,
+    candidate: C::Blesser<'a, BB>,
+}
+
+impl<R, C> BlessingStrategy for DebugLegalBlessing<R, C>
+where
+    R: BlessingStrategy<Blessing = LegalMove>,
+    C: BlessingStrategy<Blessing = LegalMove>,
+{
+    type Blessing = LegalMove;
+    type Blesser<'a, BB: BitBoard + 'a> = DebugLegalMoveBlesser<'a, BB, R, C>;
+}
+
+impl<'a, BB, R, C> MoveBlesser<'a, BB> for DebugLegalMoveBlesser<'a, BB, R, C>
+where
+    BB: BitBoard,
+    R: BlessingStrategy<Blessing = LegalMove>,
+    C: BlessingStrategy<Blessing = LegalMove>,
+{
+    type BlessedMove = LegalMove;
+
+    fn new(board: &'a BB) -> Self {
+        DebugLegalMoveBlesser {
+            reference: R::new(board),
+            candidate: C::new(board),
+        }
+    }
+
+    fn bless(&self, board: &'a BB, mv: ChessMove) -> Option<Self::BlessedMove> {
+        let reference = self.reference.bless(board, mv);
+        let candidate = self.candidate.bless(board, mv);
+
+        if reference.is_some() != candidate.is_some() {
+            panic!(
+                "legality of {mv} is {} by reference but {} by candidate in {}",
+                reference.is_some(),
+                candidate.is_some(),
+                FenBoard::from_bitboard(board)
+            );
+        }
+
+        reference
+    }
+}
+
 pub fn enumerate<'a, BB: BitBoard, X: Panopticon, L: BlessingStrategy>(
     board: &'a BB,
     buffer: &mut Vec<L::Blessing>,
+) {
+    enumerate_to::<BB, X, L>(board, !0, buffer);
+}
+
+/// Enumerate the legal moves into a [`MoveList`], without allocating.
+pub fn enumerate_into<BB, X, L>(board: &BB, list: &mut MoveList)
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    enumerate_to::<BB, X, L>(board, !0, list);
+}
+
+/// Enumerate only the moves whose destination square is in the `targets` mask.
+///
+/// Castling moves are considered to target the king's destination square, or the
+/// rook's starting square if castling is written as capturing one's own rook.
+///
+/// Moves are generated by echelon from pawns to the king, with castling last,
+/// so all king moves come after all other moves. A blessing strategy may rely
+/// on this to defer the costlier king-safety checks to the end.
+pub fn enumerate_to<BB: BitBoard, X: Panopticon, L: BlessingStrategy>(
+    board: &BB,
+    targets: u64,
+    buffer: &mut impl MoveBuffer<L::Blessing>,
+) {
+    enumerate_from_to::<BB, X, L>(board, !0, targets, buffer);
+}
+
+/// Enumerate the pseudo-legal moves of either color, regardless of whose
+/// turn it is, such as to find what the opponent threatens.
+///
+/// King safety only concerns the active player, so the moves are never
+/// blessed as legal. The moves of the inactive player are generated
+/// without en-passant, which only the active player can capture.
+pub fn enumerate_for<BB: BitBoard, X: Panopticon>(
+    board: &BB,
+    color: ChessColor,
+    buffer: &mut Vec<PseudoLegal>,
+) {
+    if board.ply().0 == color {
+        enumerate::<BB, X, NoBlessing>(board, buffer);
+    } else {
+        let mut board = board.clone();
+        board.next_ply();
+        board.set_en_passant(None);
+        enumerate::<BB, X, NoBlessing>(&board, buffer);
+    }
+}
+
+/// Enumerate only the moves of the king, which are the only
+/// moves which can be legal in double check.
+pub fn enumerate_king<BB: BitBoard, X: Panopticon, L: BlessingStrategy>(
+    board: &BB,
+    buffer: &mut Vec<L::Blessing>,
+) {
+    let pan = X::new(board.total());
+    let blesser = L::new(board);
+    let player = board.ply().0;
+
+    buffer.clear();
+
+    piece_moves(
+        board,
+        &blesser,
+        board.men(player, ChessPiece::KING),
+        board.color(player),
+        pan.king(),
+        !0,
+        buffer,
+    );
+}
+
+/// Enumerate only the moves of the given echelon for the active player.
+///
+/// Pawn moves include double pushes, en-passant and promotions,
+/// and king moves include castling.
+pub fn enumerate_piece<BB: BitBoard, X: Panopticon, L: BlessingStrategy>(
+    board: &BB,
+    ech: ChessPiece,
+    buffer: &mut Vec<L::Blessing>,
+) {
+    let total = board.total();
+    let pan = X::new(total);
+    let blesser = L::new(board);
+    let player = board.ply().0;
+    let friendly = board.color(player);
+    let men = board.men(player, ech);
+
+    buffer.clear();
+
+    match (ech, player) {
+        (ChessPiece::PAWN, ChessColor::WHITE) => {
+            pawn_moves(board, &blesser, men, pan.white_pawn(), !0, buffer)
+        }
+        (ChessPiece::PAWN, ChessColor::BLACK) => {
+            pawn_moves(board, &blesser, men, pan.black_pawn(), !0, buffer)
+        }
+        (ChessPiece::KNIGHT, _) => {
+            piece_moves(board, &blesser, men, friendly, pan.knight(), !0, buffer)
+        }
+        (ChessPiece::BISHOP, _) => {
+            piece_moves(board, &blesser, men, friendly, pan.bishop(), !0, buffer)
+        }
+        (ChessPiece::ROOK, _) => {
+            piece_moves(board, &blesser, men, friendly, pan.rook(), !0, buffer)
+        }
+        (ChessPiece::QUEEN, _) => {
+            piece_moves(board, &blesser, men, friendly, pan.queen(), !0, buffer)
+        }
+        (ChessPiece::KING, _) => {
+            piece_moves(board, &blesser, men, friendly, pan.king(), !0, buffer);
+            castling_move(board, &blesser, total, !0, buffer);
+        }
+    }
+}
+
+/// Enumerate only the moves whose origin square is in the `sources` mask.
+///
+/// Castling moves are considered to originate from the king's square.
+pub fn enumerate_from_mask<BB: BitBoard, X: Panopticon, L: BlessingStrategy>(
+    board: &BB,
+    sources: u64,
+    buffer: &mut impl MoveBuffer<L::Blessing>,
+) {
+    enumerate_from_to::<BB, X, L>(board, sources, !0, buffer);
+}
+
+/// Enumerate only the moves from a square in `sources` to a square in `targets`,
+/// see [`enumerate_to`] and [`enumerate_from_mask`].
+fn enumerate_from_to<BB: BitBoard, X: Panopticon, L: BlessingStrategy>(
+    board: &BB,
+    sources: u64,
+    targets: u64,
+    buffer: &mut impl MoveBuffer<L::Blessing>,
 ) {
     let total = board.total();
     let pan = X::new(total);
     let blesser = L::new(board);
     let player = board.ply().0;
     let friendly = board.color(player);
+    let men = |ech| board.men(player, ech) & sources;
 
     buffer.clear();
 
-    match board.ply().0 {
+    match player {
         ChessColor::WHITE => pawn_moves(
             board,
             &blesser,
-            board.men(player, ChessPiece::PAWN),
+            men(ChessPiece::PAWN),
             pan.white_pawn(),
+            targets,
             buffer,
         ),
         ChessColor::BLACK => pawn_moves(
             board,
             &blesser,
-            board.men(player, ChessPiece::PAWN),
+            men(ChessPiece::PAWN),
             pan.black_pawn(),
+            targets,
             buffer,
         ),
     }
 
+    use ChessPiece::*;
     piece_moves(
         board,
         &blesser,
-        board.men(player, ChessPiece::KNIGHT),
+        men(KNIGHT),
         friendly,
         pan.knight(),
+        targets,
         buffer,
     );
-
     piece_moves(
         board,
         &blesser,
-        board.men(player, ChessPiece::BISHOP),
+        men(BISHOP),
         friendly,
         pan.bishop(),
+        targets,
         buffer,
     );
-
     piece_moves(
         board,
         &blesser,
-        board.men(player, ChessPiece::ROOK),
+        men(ROOK),
         friendly,
         pan.rook(),
+        targets,
         buffer,
     );
-
     piece_moves(
         board,
         &blesser,
-        board.men(player, ChessPiece::QUEEN),
+        men(QUEEN),
         friendly,
         pan.queen(),
+        targets,
         buffer,
     );
-
     piece_moves(
         board,
         &blesser,
-        board.men(player, ChessPiece::KING),
+        men(KING),
         friendly,
         pan.king(),
+        targets,
         buffer,
     );
 
-    castling_move(board, &blesser, total, buffer);
+    if men(KING) != 0 {
+        castling_move(board, &blesser, total, targets, buffer);
+    }
+}
+
+/// Keep the legal moves among pseudo-legal moves generated elsewhere,
+/// checking that the king is not left in check and that castling is safe.
+pub fn filter_legal<BB: BitBoard, X: Panopticon>(
+    board: &BB,
+    pseudo: &[PseudoLegal],
+) -> Vec<LegalMove> {
+    let blesser = LegalBlessing::<CachedAttackStrategy<X>>::new(board);
+    pseudo
+        .iter()
+        .filter_map(|mv| blesser.bless(board, mv.0))
+        .collect()
+}
+
+/// The legal _en passant_ captures of the active player, of which there are at most two.
+pub fn legal_en_passant<BB, X, L>(board: &BB) -> Vec<LegalMove>
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    let (target, _) = EnPassant::bit_sq(board.trans().en_passant);
+    if target == 0 {
+        return vec![];
+    }
+
+    let mut moves = vec![];
+    enumerate_to::<BB, X, L>(board, target, &mut moves);
+    moves.retain(|mv| mv.0.ech == ChessPiece::PAWN && mv.0.special == Some(SpecialMove::PAWN));
+    moves
+}
+
+/// Enumerate the tactical moves of the active player, those being captures,
+/// promotions, and checks, such as for quiescence search.
+///
+/// Each move occurs once, even if it is for instance both a capture and a check.
+pub fn enumerate_tactical<BB, X, L>(board: &BB, buffer: &mut impl MoveBuffer<LegalMove>)
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    let player = board.ply().0;
+    let enemy = board.color(player.opp());
+    let (en_passant, _) = EnPassant::bit_sq(board.trans().en_passant);
+    let promotion_rank = match player {
+        ChessColor::WHITE => rank_mask(BoardRank::_8),
+        ChessColor::BLACK => rank_mask(BoardRank::_1),
+    };
+
+    buffer.clear();
+
+    // Captures and promotions can only land on these squares
+    let mut moves = vec![];
+    enumerate_to::<BB, X, L>(board, enemy | en_passant | promotion_rank, &mut moves);
+    for mv in moves {
+        if mv.0.capture.is_some() || PawnPromotion::from_special(mv.0.special).is_some() {
+            buffer.push(mv);
+        }
+    }
+
+    // Quiet moves, including those to the empty en passant square,
+    // must be tried for giving check
+    let mut moves = vec![];
+    enumerate_to::<BB, X, L>(board, !enemy, &mut moves);
+    for mv in moves {
+        if mv.0.capture.is_none()
+            && PawnPromotion::from_special(mv.0.special).is_none()
+            && king_in_check::<BB, X>(
+                &clone_make_legal_move::<BB, NoHashes>(board, mv),
+                player.opp(),
+            )
+        {
+            buffer.push(mv);
+        }
+    }
+}
+
+/// Whether a single candidate move is legal, without enumerating all moves,
+/// such as when validating a best move remembered from another position.
+///
+/// The echelon, capture and special move must all agree with the board,
+/// as they would for a move produced by [`enumerate`].
+pub fn is_legal<BB, X, L>(board: &BB, mv: ChessMove) -> bool
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    let player = board.ply().0;
+    let friendly = board.color(player);
+    let from = mv.from;
+    let to = 1 << mv.to.ix();
+
+    if friendly & 1 << from.ix() == 0 {
+        return false;
+    }
+
+    let promotion = PawnPromotion::from_special(mv.special);
+    if ChessMove::from_squares(board, mv.from, mv.to, promotion) != Some(mv) {
+        return false;
+    }
+
+    let pan = X::new(board.total());
+
+    let reachable = if let Some(dir) = CastlingDirection::from_special(mv.special) {
+        let castling = board.castling();
+        board.trans().rights[player.ix()][dir.ix()]
+            && castling.space[dir.ix()] & castling.back_rank[player.ix()] & board.total() == 0
+    } else if mv.ech == ChessPiece::PAWN {
+        let eps = EnPassant::bit_sq(board.trans().en_passant).0;
+        let enemy = board.color(player.opp()) | eps;
+        let targets = match player {
+            ChessColor::WHITE => pan.white_pawn().push(from) | pan.white_pawn().hits(from, enemy),
+            ChessColor::BLACK => pan.black_pawn().push(from) | pan.black_pawn().hits(from, enemy),
+        };
+        let promotes = mv.to <= Square::h1 || Square::a8 <= mv.to;
+        targets & to != 0 && promotes == promotion.is_some()
+    } else {
+        let targets = match mv.ech {
+            ChessPiece::KNIGHT => pan.knight().hits(from, friendly),
+            ChessPiece::BISHOP => pan.bishop().hits(from, friendly),
+            ChessPiece::ROOK => pan.rook().hits(from, friendly),
+            ChessPiece::QUEEN => pan.queen().hits(from, friendly),
+            _ => pan.king().hits(from, friendly),
+        };
+        targets & to != 0 && mv.special.is_none()
+    };
+
+    reachable && L::new(board).bless(board, mv).is_some()
+}
+
+/// Why a pseudo-legal move is not legal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IllegalReason {
+    /// The move leaves or puts the king of the moving player in check.
+    LeavesKingInCheck,
+    /// Castling while in check.
+    CastleOutOfCheck,
+    /// Castling across a square attacked by the opponent.
+    CastleThroughCheck,
+}
+
+/// Enumerate all pseudo-legal moves of the active player, annotating
+/// the illegal ones with the reason they are illegal, for explaining the rules.
+pub fn annotated_pseudolegal<BB: BitBoard, X: Panopticon>(
+    board: &BB,
+) -> Vec<(ChessMove, Option<IllegalReason>)> {
+    let player = board.ply().0;
+    let attack = attacks::<BB, X>(board, player.opp()).attack;
+    let castling = board.castling();
+
+    let mut moves = vec![];
+    enumerate::<BB, X, NoBlessing>(board, &mut moves);
+
+    moves
+        .into_iter()
+        .map(|PseudoLegal(mv)| {
+            let reason = if let Some(dir) = CastlingDirection::from_special(mv.special) {
+                if king_in_check::<BB, X>(board, player) {
+                    Some(IllegalReason::CastleOutOfCheck)
+                } else if attack & castling.safety[dir.ix()] & castling.back_rank[player.ix()] != 0
+                {
+                    Some(IllegalReason::CastleThroughCheck)
+                } else {
+                    None
+                }
+            } else {
+                let after = clone_make_pseudolegal_move(board, PseudoLegal(mv));
+                king_in_check::<BB, X>(&after, player).then_some(IllegalReason::LeavesKingInCheck)
+            };
+
+            (mv, reason)
+        })
+        .collect()
+}
+
+/// How a legal move gets the king out of check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EscapeKind {
+    /// The king steps out of check, possibly capturing.
+    KingMove,
+    /// Another chessman is interposed between the king and a sliding checker.
+    Block,
+    /// Another chessman captures the checking chessman.
+    CaptureChecker,
+}
+
+/// Enumerate the legal moves of the active player when in check,
+/// classifying how each escapes. Returns nothing when not in check.
+pub fn check_escapes<BB, X, L>(board: &BB) -> Vec<(LegalMove, EscapeKind)>
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    let player = board.ply().0;
+    let checkers = checkers::<BB, X>(board, player);
+    if checkers == 0 {
+        return vec![];
+    }
+
+    let mut moves = vec![];
+    enumerate::<BB, X, L>(board, &mut moves);
+
+    moves
+        .into_iter()
+        .map(|LegalMove(mv)| {
+            let captured = match board.trans().en_passant {
+                Some(ep) if mv.ech == ChessPiece::PAWN && mv.to == ep.target_square() => {
+                    ep.captured_square()
+                }
+                _ => mv.to,
+            };
+
+            let kind = if mv.ech == ChessPiece::KING {
+                EscapeKind::KingMove
+            } else if mv.capture.is_some() && checkers & 1 << captured.ix() != 0 {
+                EscapeKind::CaptureChecker
+            } else {
+                EscapeKind::Block
+            };
+
+            (LegalMove(mv), kind)
+        })
+        .collect()
 }
 
 pub fn pawn_moves<'a, P: PawnVision, BB: BitBoard, L: MoveBlesser<'a, BB>>(
@@ -184,13 +728,14 @@ pub fn pawn_moves<'a, P: PawnVision, BB: BitBoard, L: MoveBlesser<'a, BB>>(
     blesser: &L,
     pawns: u64,
     pawn_vision: P,
-    buffer: &mut Vec<L::BlessedMove>,
+    targets: u64,
+    buffer: &mut impl MoveBuffer<L::BlessedMove>,
 ) {
     let eps = EnPassant::bit_sq(board.trans().en_passant);
     let enemy = board.color(board.ply().0.opp()) | eps.0;
 
     biterate! {for from in pawns; {
-        biterate! {for to in pawn_vision.push(from); {
+        biterate! {for to in pawn_vision.push(from) & targets; {
             let mut mv = ChessMove {
                 from, to,
                 ech: ChessPiece::PAWN,
@@ -205,7 +750,7 @@ pub fn pawn_moves<'a, P: PawnVision, BB: BitBoard, L: MoveBlesser<'a, BB>>(
             promotions(board, blesser, mv, buffer);
         }}
 
-        biterate! {for to in pawn_vision.hits(from, enemy); {
+        biterate! {for to in pawn_vision.hits(from, enemy) & targets; {
             let mut mv = ChessMove {
                 from, to,
                 ech: ChessPiece::PAWN,
@@ -228,10 +773,10 @@ fn promotions<'a, BB: BitBoard, L: MoveBlesser<'a, BB>>(
     board: &'a BB,
     blesser: &L,
     mut mv: ChessMove,
-    buffer: &mut Vec<L::BlessedMove>,
+    buffer: &mut impl MoveBuffer<L::BlessedMove>,
 ) {
     use SpecialMove::*;
-    if mv.to < Square::a1 || Square::h7 < mv.to {
+    if mv.to <= Square::h1 || Square::a8 <= mv.to {
         for spc in [KNIGHT, BISHOP, ROOK, QUEEN] {
             mv.special = Some(spc);
 
@@ -248,10 +793,11 @@ fn piece_moves<'a, P: PieceVision, BB: BitBoard, L: MoveBlesser<'a, BB>>(
     pieces: u64,
     friendly: u64,
     piece: P,
-    buffer: &mut Vec<L::BlessedMove>,
+    targets: u64,
+    buffer: &mut impl MoveBuffer<L::BlessedMove>,
 ) {
     biterate! {for from in pieces; {
-        biterate! {for to in piece.hits(from, friendly); {
+        biterate! {for to in piece.hits(from, friendly) & targets; {
             let mut mv = ChessMove {
                 from, to,
                 ech: ChessPiece::from(P::ID),
@@ -268,7 +814,8 @@ fn castling_move<'a, BB: BitBoard, L: MoveBlesser<'a, BB>>(
     board: &'a BB,
     blesser: &L,
     total: u64,
-    buffer: &mut Vec<L::BlessedMove>,
+    targets: u64,
+    buffer: &mut impl MoveBuffer<L::BlessedMove>,
 ) {
     use CastlingDirection::*;
 
@@ -285,9 +832,20 @@ fn castling_move<'a, BB: BitBoard, L: MoveBlesser<'a, BB>>(
             continue;
         }
 
+        // Chess960 interfaces commonly write castling as the king capturing its own rook
+        let to = if castling.rules.capture_own_rook {
+            castling.rules.rook_start[player.ix()][dir.ix()]
+        } else {
+            castling.rules.king_end[player.ix()][dir.ix()]
+        };
+
+        if (targets & 1 << to.ix()) == 0 {
+            continue;
+        }
+
         let mv = ChessMove {
             from: castling.rules.king_start[player.ix()],
-            to: castling.rules.king_end[player.ix()][dir.ix()],
+            to,
             ech: ChessPiece::KING,
             special: Some(SpecialMove::from(dir)),
             capture: None,
@@ -296,3 +854,746 @@ fn castling_move<'a, BB: BitBoard, L: MoveBlesser<'a, BB>>(
         blesser.bless_into(board, mv, buffer);
     }
 }
+
+#[test]
+fn enumerate_to_test() {
+    use crate::{
+        bitboard::board::FullerBitBoard,
+        bitboard::{attacking::FakeMoveSimplStrategy, hash::FullZobristTables, vision::MostlyBits},
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    let board = FenBoard::parser()
+        .parse("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")
+        .into_result()
+        .unwrap()
+        .as_bitboard::<FullerBitBoard, FullZobristTables>();
+
+    let center =
+        1 << Square::d4.ix() | 1 << Square::e4.ix() | 1 << Square::d5.ix() | 1 << Square::e5.ix();
+
+    let mut moves = vec![];
+    enumerate_to::<_, MostlyBits, LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>>(
+        &board, center, &mut moves,
+    );
+
+    let mut moves = moves
+        .into_iter()
+        .map(|mv| (mv.0.from, mv.0.to))
+        .collect::<Vec<_>>();
+    moves.sort();
+
+    assert_eq!(
+        moves,
+        vec![(Square::d2, Square::d4), (Square::f3, Square::d4), (Square::f3, Square::e5),]
+    );
+}
+
+#[test]
+fn enumerate_piece_test() {
+    use crate::{
+        bitboard::board::FullerBitBoard,
+        bitboard::{attacking::FakeMoveSimplStrategy, hash::FullZobristTables, vision::MostlyBits},
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+    use strum::VariantArray;
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let board = FenBoard::parser()
+        .parse("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+        .into_result()
+        .unwrap()
+        .as_bitboard::<FullerBitBoard, FullZobristTables>();
+
+    let mut moves = vec![];
+    enumerate_piece::<_, MostlyBits, Legal>(&board, ChessPiece::ROOK, &mut moves);
+    assert_eq!(moves.len(), 5);
+    assert!(moves.iter().all(|mv| mv.0.ech == ChessPiece::ROOK));
+
+    enumerate_piece::<_, MostlyBits, Legal>(&board, ChessPiece::KING, &mut moves);
+    assert_eq!(moves.len(), 4);
+    assert_eq!(
+        moves
+            .iter()
+            .filter(|mv| CastlingDirection::from_special(mv.0.special).is_some())
+            .count(),
+        2
+    );
+
+    let mut all = vec![];
+    enumerate::<_, MostlyBits, Legal>(&board, &mut all);
+    let total: usize = ChessPiece::VARIANTS
+        .iter()
+        .map(|&ech| {
+            enumerate_piece::<_, MostlyBits, Legal>(&board, ech, &mut moves);
+            moves.len()
+        })
+        .sum();
+    assert_eq!(total, all.len());
+}
+
+#[test]
+fn annotated_pseudolegal_test() {
+    use crate::{
+        bitboard::board::FullerBitBoard,
+        bitboard::{attacking::FakeMoveSimplStrategy, hash::FullZobristTables, vision::MostlyBits},
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let annotated = |fen: &str| {
+        let board = FenBoard::parser()
+            .parse(fen)
+            .into_result()
+            .unwrap()
+            .as_bitboard::<FullerBitBoard, FullZobristTables>();
+
+        let mut legal = vec![];
+        enumerate::<_, MostlyBits, Legal>(&board, &mut legal);
+        let moves = annotated_pseudolegal::<_, MostlyBits>(&board);
+        for (mv, reason) in &moves {
+            assert_eq!(
+                reason.is_none(),
+                legal.iter().any(|lm| lm.0 == *mv),
+                "{mv} {reason:?}"
+            );
+        }
+        moves
+    };
+
+    let moves = annotated("4k3/4r3/8/8/8/8/4N3/4K2R w K - 0 1");
+    let knight = moves
+        .iter()
+        .filter(|(mv, _)| mv.ech == ChessPiece::KNIGHT)
+        .collect::<Vec<_>>();
+    assert_eq!(knight.len(), 6);
+    assert!(
+        knight
+            .iter()
+            .all(|(_, reason)| *reason == Some(IllegalReason::LeavesKingInCheck))
+    );
+    assert!(moves.iter().any(|(_, reason)| reason.is_none()));
+
+    let moves = annotated("4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+    assert!(moves.iter().all(|(_, reason)| reason.is_none()));
+
+    let moves = annotated("4k3/8/8/8/8/8/5r2/4K2R w K - 0 1");
+    let castle = moves
+        .iter()
+        .find(|(mv, _)| CastlingDirection::from_special(mv.special).is_some())
+        .unwrap();
+    assert_eq!(castle.1, Some(IllegalReason::CastleThroughCheck));
+
+    let moves = annotated("4k3/8/8/8/8/8/8/r3K2R w K - 0 1");
+    let castle = moves
+        .iter()
+        .find(|(mv, _)| CastlingDirection::from_special(mv.special).is_some())
+        .unwrap();
+    assert_eq!(castle.1, Some(IllegalReason::CastleOutOfCheck));
+}
+
+#[test]
+fn debug_legal_blessing_test() {
+    use crate::{
+        bitboard::{
+            attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+            moving::clone_make_legal_move, vision::MostlyBits,
+        },
+        notation::Parsable,
+    };
+    use chumsky::Parser;
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+    type Cached = LegalBlessing<CachedAttackStrategy<MostlyBits>>;
+    type Debug = DebugLegalBlessing<Legal, Cached>;
+
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "3rkb2/3p1p2/3N4/8/8/8/8/4R1K1 b - - 0 1",
+    ];
+
+    for fen in fens {
+        let board = FenBoard::parser()
+            .parse(fen)
+            .into_result()
+            .unwrap()
+            .as_bitboard::<FullerBitBoard, FullZobristTables>();
+
+        let mut moves = vec![];
+        let mut replies = vec![];
+        let mut reference = vec![];
+        enumerate::<_, MostlyBits, Debug>(&board, &mut moves);
+        enumerate::<_, MostlyBits, Legal>(&board, &mut reference);
+        assert_eq!(moves, reference, "{fen}");
+
+        for mv in moves {
+            let board = clone_make_legal_move::<_, FullZobristTables>(&board, mv);
+            enumerate::<_, MostlyBits, Debug>(&board, &mut replies);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "legality of")]
+fn debug_legal_blessing_mismatch_test() {
+    use crate::{
+        bitboard::{
+            attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+            vision::MostlyBits,
+        },
+        notation::Parsable,
+    };
+    use chumsky::Parser;
+
+    struct Lenient;
+    struct LenientBlesser;
+
+    impl BlessingStrategy for Lenient {
+        type Blessing = LegalMove;
+        type Blesser<'a, BB: BitBoard + 'a> = LenientBlesser;
+    }
+
+    impl<'a, BB: BitBoard> MoveBlesser<'a, BB> for LenientBlesser {
+        type BlessedMove = LegalMove;
+
+        fn new(board: &'a BB) -> Self {
+            LenientBlesser
+        }
+
+        fn bless(&self, board: &'a BB, mv: ChessMove) -> Option<Self::BlessedMove> {
+            Some(LegalMove(mv))
+        }
+    }
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    // Double check, so most pseudo-legal moves are illegal
+    let board = FenBoard::parser()
+        .parse("3rkb2/3p1p2/3N4/8/8/8/8/4R1K1 b - - 0 1")
+        .into_result()
+        .unwrap()
+        .as_bitboard::<FullerBitBoard, FullZobristTables>();
+
+    let mut moves = vec![];
+    enumerate::<_, MostlyBits, DebugLegalBlessing<Legal, Lenient>>(&board, &mut moves);
+}
+
+#[test]
+fn check_escapes_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+        setup::parse_position, vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let (board, _, _) =
+        parse_position::<FullerBitBoard, FullZobristTables>("7k/8/8/4r3/8/R4N2/2B5/4K3 w - - 0 1")
+            .unwrap();
+
+    let escapes = check_escapes::<_, MostlyBits, Legal>(&board);
+    let mut legal = vec![];
+    enumerate::<_, MostlyBits, Legal>(&board, &mut legal);
+    assert_eq!(escapes.len(), legal.len());
+
+    let of_kind = |kind| {
+        let mut res = escapes
+            .iter()
+            .filter(|(_, k)| *k == kind)
+            .map(|(mv, _)| (mv.0.from, mv.0.to))
+            .collect::<Vec<_>>();
+        res.sort();
+        res
+    };
+
+    use Square::*;
+    assert_eq!(
+        of_kind(EscapeKind::KingMove),
+        vec![(e1, d1), (e1, f1), (e1, d2), (e1, f2)]
+    );
+    assert_eq!(of_kind(EscapeKind::Block), vec![(c2, e4), (a3, e3)]);
+    assert_eq!(of_kind(EscapeKind::CaptureChecker), vec![(f3, e5)]);
+
+    let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    )
+    .unwrap();
+    assert!(check_escapes::<_, MostlyBits, Legal>(&board).is_empty());
+}
+
+#[test]
+fn enumerate_from_mask_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+        setup::parse_position, vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    )
+    .unwrap();
+
+    let mut moves = vec![];
+    let knights = board.men(ChessColor::WHITE, ChessPiece::KNIGHT);
+    enumerate_from_mask::<_, MostlyBits, Legal>(&board, knights, &mut moves);
+    assert_eq!(moves.len(), 11);
+    assert!(moves.iter().all(|mv| mv.0.ech == ChessPiece::KNIGHT));
+
+    let mut expected = vec![];
+    enumerate_piece::<_, MostlyBits, Legal>(&board, ChessPiece::KNIGHT, &mut expected);
+    assert_eq!(moves, expected);
+
+    let king = board.men(ChessColor::WHITE, ChessPiece::KING);
+    enumerate_from_mask::<_, MostlyBits, Legal>(&board, king, &mut moves);
+    assert_eq!(moves.len(), 4);
+
+    enumerate_from_mask::<_, MostlyBits, Legal>(&board, !0, &mut moves);
+    assert_eq!(moves.len(), 48);
+
+    enumerate_from_mask::<_, MostlyBits, Legal>(&board, 0, &mut moves);
+    assert!(moves.is_empty());
+}
+
+#[test]
+fn is_legal_test() {
+    use rand::{Rng, SeedableRng, rngs::SmallRng};
+    use strum::VariantArray;
+
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+        setup::parse_position, vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "r3k2r/8/8/8/8/8/8/R3K1R1 b Qkq - 0 1",
+    ];
+
+    use PawnPromotion::*;
+    const PROMOTIONS: [PawnPromotion; 4] = [KNIGHT, BISHOP, ROOK, QUEEN];
+
+    let mut rng = SmallRng::seed_from_u64(2155);
+
+    for fen in fens {
+        let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+        let mut legal = vec![];
+        enumerate::<_, MostlyBits, Legal>(&board, &mut legal);
+
+        for LegalMove(mv) in &legal {
+            assert!(is_legal::<_, MostlyBits, Legal>(&board, *mv), "{fen} {mv}");
+        }
+
+        for _ in 0..5000 {
+            let mut mv = ChessMove {
+                ech: ChessPiece::VARIANTS[rng.random_range(0..6)],
+                from: Square::from_u8(rng.random_range(0..64)),
+                to: Square::from_u8(rng.random_range(0..64)),
+                special: None,
+                capture: None,
+            };
+
+            // Mostly moves which agree with the board, to reach the geometry checks
+            if rng.random_bool(0.8) {
+                let promotion = rng
+                    .random_bool(0.3)
+                    .then(|| PROMOTIONS[rng.random_range(0..4)]);
+                match ChessMove::from_squares(&board, mv.from, mv.to, promotion) {
+                    Some(m) => mv = m,
+                    None => continue,
+                }
+            }
+
+            assert_eq!(
+                is_legal::<_, MostlyBits, Legal>(&board, mv),
+                legal.contains(&LegalMove(mv)),
+                "{fen} {mv}"
+            );
+        }
+    }
+}
+
+#[test]
+fn filter_legal_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+        setup::parse_position, vision::MostlyBits,
+    };
+
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "8/8/8/K2pP2q/8/8/8/7k w - d6 0 1",
+    ] {
+        let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+
+        let mut pseudo = vec![];
+        enumerate::<_, MostlyBits, NoBlessing>(&board, &mut pseudo);
+        let mut legal = vec![];
+        enumerate::<_, MostlyBits, LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>>(
+            &board, &mut legal,
+        );
+
+        assert!(pseudo.len() >= legal.len());
+        assert_eq!(
+            filter_legal::<_, MostlyBits>(&board, &pseudo),
+            legal,
+            "{fen}"
+        );
+    }
+}
+
+#[test]
+fn legal_en_passant_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+        setup::parse_position, vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let en_passant = |fen| {
+        let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+        legal_en_passant::<_, MostlyBits, Legal>(&board)
+            .into_iter()
+            .map(|mv| (mv.0.from, mv.0.to))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        en_passant("4k3/8/8/2PpP3/8/8/8/4K3 w - d6 0 1"),
+        [(Square::c5, Square::d6), (Square::e5, Square::d6)]
+    );
+    assert_eq!(
+        en_passant("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3"),
+        [(Square::e5, Square::f6)]
+    );
+    assert_eq!(
+        en_passant("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+        []
+    );
+
+    // Capturing would expose the king along the fifth rank
+    let pinned = "8/8/8/K2pP2q/8/8/8/7k w - d6 0 1";
+    assert_eq!(en_passant(pinned), []);
+
+    let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(pinned).unwrap();
+    let mut pseudo = vec![];
+    enumerate_to::<_, MostlyBits, NoBlessing>(&board, 1 << Square::d6.ix(), &mut pseudo);
+    assert_eq!(pseudo.len(), 1);
+}
+
+#[test]
+fn enumerate_into_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+        setup::parse_position, vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let mut list = MoveList::new();
+    for (fen, count) in [
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            20,
+        ),
+        (
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            48,
+        ),
+        ("R6R/3Q4/1Q4Q1/4Q3/2Q4Q/Q4Q2/pp1Q4/kBNN1KB1 w - - 0 1", 218),
+    ] {
+        let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+        let mut moves = vec![];
+        enumerate::<_, MostlyBits, Legal>(&board, &mut moves);
+
+        enumerate_into::<_, MostlyBits, Legal>(&board, &mut list);
+        assert_eq!(list.len(), count);
+        assert_eq!(list.as_slice(), moves);
+        assert!(list.iter().eq(moves.iter()));
+    }
+}
+
+#[test]
+fn check_aware_blessing_test() {
+    use crate::bitboard::{
+        attacking::{CachedAttackStrategy, FakeMoveSimplStrategy},
+        board::FullerBitBoard,
+        hash::FullZobristTables,
+        moving::clone_make_legal_move,
+        setup::parse_position,
+        vision::MostlyBits,
+    };
+
+    fn check_flags<AS: AttackMaskStrategy>(board: &FullerBitBoard) -> Vec<(LegalMove, bool)> {
+        let mut moves = vec![];
+        enumerate::<_, MostlyBits, CheckAwareBlessing<AS>>(board, &mut moves);
+        moves
+    }
+
+    for fen in [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "5k2/8/8/8/8/8/8/4K2R w K - 0 1",
+        // Discovered checks, checking promotions and an en passant discovered check
+        "4k3/8/8/8/8/8/4B3/4RK2 w - - 0 1",
+        "1k6/5P2/8/8/8/8/8/4K3 w - - 0 1",
+        "8/8/8/k2pP2R/8/8/8/4K3 w - d6 0 1",
+    ] {
+        let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+
+        let flagged = check_flags::<FakeMoveSimplStrategy<MostlyBits>>(&board);
+        assert_eq!(
+            flagged,
+            check_flags::<CachedAttackStrategy<MostlyBits>>(&board)
+        );
+
+        let mut legal = vec![];
+        enumerate::<_, MostlyBits, LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>>(
+            &board, &mut legal,
+        );
+        assert_eq!(flagged.iter().map(|(mv, _)| *mv).collect::<Vec<_>>(), legal);
+
+        for (mv, check) in flagged {
+            let after = clone_make_legal_move::<_, FullZobristTables>(&board, mv);
+            assert_eq!(
+                check,
+                king_in_check::<_, MostlyBits>(&after, ChessColor::BLACK),
+                "{fen} {}",
+                mv.0
+            );
+        }
+    }
+
+    // Castling kingside gives check with the rook on f1
+    let (board, _, _) =
+        parse_position::<FullerBitBoard, FullZobristTables>("5k2/8/8/8/8/8/8/4K2R w K - 0 1")
+            .unwrap();
+    let flagged = check_flags::<FakeMoveSimplStrategy<MostlyBits>>(&board);
+    let castle = flagged
+        .iter()
+        .find(|(mv, _)| CastlingDirection::from_special(mv.0.special).is_some())
+        .unwrap();
+    assert!(castle.1);
+    assert!(flagged.iter().any(|(_, check)| !check));
+}
+
+#[test]
+fn castling_capture_own_rook_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy,
+        board::{FullerBitBoard, HasDefaultMetaBoard},
+        castling::BitCastling,
+        hash::FullZobristTables,
+        moving::clone_make_legal_move,
+        setup::{apply_castling_rights, board_field, parse_position},
+        vision::MostlyBits,
+    };
+    use crate::notation::fen::ColorCase;
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    static CHESS_960: BitCastling = BitCastling::from_rules(CHESS_960_RKNBBQNR);
+
+    let (mut board, _, _) =
+        parse_position::<FullerBitBoard, FullZobristTables>("rk5r/8/8/8/8/8/8/RK5R w - - 0 1")
+            .unwrap();
+    board.metaboard_mut().castling = &CHESS_960;
+    apply_castling_rights::<_, FullZobristTables>(
+        &mut board,
+        &[
+            ColorCase::White(CastlingDirection::EAST),
+            ColorCase::White(CastlingDirection::WEST),
+        ],
+    );
+
+    let mut moves = vec![];
+    enumerate::<_, MostlyBits, Legal>(&board, &mut moves);
+
+    let castles = moves
+        .iter()
+        .filter(|mv| CastlingDirection::from_special(mv.0.special).is_some())
+        .map(|mv| (mv.0.from, mv.0.to))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        castles,
+        [(Square::b1, Square::a1), (Square::b1, Square::h1)]
+    );
+
+    for (to, field) in
+        [(Square::a1, "rk5r/8/8/8/8/8/8/2KR3R"), (Square::h1, "rk5r/8/8/8/8/8/8/R4RK1")]
+    {
+        let mv = *moves
+            .iter()
+            .find(|mv| mv.0.to == to && mv.0.ech == ChessPiece::KING)
+            .unwrap();
+        mv.0.sanity_check(ChessColor::WHITE, &CHESS_960.rules);
+        assert!(is_legal::<_, MostlyBits, Legal>(&board, mv.0));
+
+        let after = clone_make_legal_move::<_, FullZobristTables>(&board, mv);
+        assert_eq!(board_field(&after), field);
+    }
+}
+
+#[test]
+fn enumerate_king_last_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+        setup::parse_position, vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+        "8/8/8/K2pP2q/8/8/8/7k w - d6 0 1",
+    ] {
+        let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+        let mut moves = vec![];
+        enumerate::<_, MostlyBits, Legal>(&board, &mut moves);
+
+        let first_king = moves
+            .iter()
+            .position(|mv| mv.0.ech == ChessPiece::KING)
+            .unwrap_or(moves.len());
+        assert!(
+            moves[first_king..]
+                .iter()
+                .all(|mv| mv.0.ech == ChessPiece::KING),
+            "{fen}"
+        );
+    }
+}
+
+#[test]
+fn enumerate_tactical_test() {
+    use std::collections::HashSet;
+
+    use crate::{
+        bitboard::{
+            attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+            setup::parse_position, vision::MostlyBits,
+        },
+        notation::CoordNotation,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    for (fen, expected) in [
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            vec![],
+        ),
+        // Kiwipete: eight captures, no promotions or checks
+        (
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            vec!["d5e6", "e5d7", "e5f7", "e5g6", "f3f6", "f3h3", "e2a6", "g2h3"],
+        ),
+        // cxd8=Q+ is a capture, a promotion and a check all at once,
+        // and castling gives check with the rook
+        (
+            "3r1k2/2P5/8/8/8/8/8/4K2R w K - 0 1",
+            vec![
+                "c7d8q", "c7d8r", "c7d8b", "c7d8n", "c7c8q", "c7c8r", "c7c8b", "c7c8n", "h1h8",
+                "h1f1", "e1g1",
+            ],
+        ),
+        // Nd6+ is a quiet check onto the empty en passant square
+        (
+            "4k3/8/8/3pP3/4N3/8/8/4K3 w - d6 0 2",
+            vec!["e5d6", "e4f6", "e4d6"],
+        ),
+    ] {
+        let (board, player, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+
+        let mut moves = vec![];
+        enumerate::<_, MostlyBits, Legal>(&board, &mut moves);
+        let filtered = moves
+            .iter()
+            .copied()
+            .filter(|&mv| {
+                mv.0.capture.is_some()
+                    || PawnPromotion::from_special(mv.0.special).is_some()
+                    || king_in_check::<_, MostlyBits>(
+                        &clone_make_legal_move::<_, NoHashes>(&board, mv),
+                        player.opp(),
+                    )
+            })
+            .collect::<HashSet<_>>();
+
+        let mut tactical = vec![];
+        enumerate_tactical::<_, MostlyBits, Legal>(&board, &mut tactical);
+        let set = tactical.iter().copied().collect::<HashSet<_>>();
+        assert_eq!(tactical.len(), set.len(), "{fen}");
+        assert_eq!(set, filtered, "{fen}");
+
+        let mut tactical = tactical
+            .iter()
+            .map(|mv| CoordNotation::from(mv.0).to_string())
+            .collect::<Vec<_>>();
+        tactical.sort();
+
+        let mut expected = expected;
+        expected.sort();
+        assert_eq!(tactical, expected, "{fen}");
+    }
+}
+
+#[test]
+fn enumerate_for_test() {
+    use crate::bitboard::{
+        board::FullerBitBoard, hash::FullZobristTables, setup::parse_position, vision::MostlyBits,
+    };
+
+    let (board, _, _) =
+        parse_position::<FullerBitBoard, FullZobristTables>("4k3/8/8/8/3n4/8/2R5/4K3 w - - 0 1")
+            .unwrap();
+
+    let mut own = vec![];
+    enumerate::<_, MostlyBits, NoBlessing>(&board, &mut own);
+    let mut white = vec![];
+    enumerate_for::<_, MostlyBits>(&board, ChessColor::WHITE, &mut white);
+    assert_eq!(white, own);
+
+    let mut black = vec![];
+    enumerate_for::<_, MostlyBits>(&board, ChessColor::BLACK, &mut black);
+    let threats = black
+        .iter()
+        .filter(|mv| mv.0.ech == ChessPiece::KNIGHT)
+        .map(|mv| mv.0.to)
+        .collect::<Vec<_>>();
+    assert_eq!(threats.len(), 8);
+    assert!(threats.contains(&Square::e2));
+    assert!(
+        black
+            .iter()
+            .any(|mv| mv.0.to == Square::c2 && mv.0.capture == Some(ChessCommoner::ROOK))
+    );
+    assert!(
+        black
+            .iter()
+            .all(|mv| matches!(mv.0.ech, ChessPiece::KNIGHT | ChessPiece::KING))
+    );
+}
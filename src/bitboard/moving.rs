@@ -11,7 +11,7 @@
 //! Additionally:
 //!
 //! - a simple and promotion and special pawn move can be with captures, see [`capturing_move`]
-//! - a move that either moves or captures a rook results in a loss of castling rights, see [`rook_rights_loss`]
+//! - a move of the king, or a move or capture of a rook, may forfeit castling rights, see [`rights_loss`]
 //!
 //! These are combined into all the utilities needed to make moves on a chessboard, and
 //! are implemented using the [`BitBoard`] trait as a visitor pattern. Two additonal visitors
@@ -41,11 +41,13 @@ pub fn make_legal_move<BB: BitBoard, ZT: ZobristTables>(
 
     let res = board.trans();
 
+    rights_loss(board, mv.0, zobristhashes);
     simple_move(board, mv.0, zobristhashes);
     promotion_move(board, mv.0, zobristhashes);
     pawn_special(board, mv.0, zobristhashes);
     castling_move(board, mv.0, zobristhashes);
 
+    board.hash(zobristhashes.black());
     board.next_ply();
 
     return res;
@@ -65,7 +67,9 @@ pub fn unmake_legal_move<BB: BitBoard, ZT: ZobristTables>(
     board.set_transients(trans);
 
     board.prev_ply();
+    board.hash(zobristhashes.black());
 
+    rights_loss(board, mv.0, zobristhashes);
     simple_move(board, mv.0, zobristhashes);
     promotion_move(board, mv.0, zobristhashes);
     pawn_special(board, mv.0, zobristhashes);
@@ -84,6 +88,19 @@ pub fn clone_make_legal_move<BB: BitBoard + Clone, ZT: ZobristTables>(
     res
 }
 
+/// Walk a game, lazily yielding the board after each move.
+///
+/// The moves must be legal in turn, as for [`make_legal_move`].
+pub fn positions<'a, BB: BitBoard + Clone + 'a, ZT: ZobristTables>(
+    start: BB,
+    moves: &'a [ChessMove],
+) -> impl Iterator<Item = BB> + 'a {
+    moves.iter().scan(start, |board, &mv| {
+        make_legal_move::<BB, ZT>(board, LegalMove(mv));
+        Some(board.clone())
+    })
+}
+
 /// Clone the board and make the pseudo-legal move on the clone, without
 /// accounting for anything except the board position.
 ///
@@ -96,21 +113,422 @@ pub fn clone_make_pseudolegal_move<BB: BitBoard + Clone>(board: &BB, mv: PseudoL
 
 /// Compute the zobrist hash that would result from applying this move.
 pub fn hash_prospective_move<BB: BitBoard, ZT: ZobristTables>(board: &BB, mv: ChessMove) -> u64 {
+    board.curr_hash() ^ move_delta::<ZT>(board.ply().0, mv, board.trans(), board.castling())
+}
+
+/// Compute the zobrist hash that would result from applying a sequence of moves,
+/// without making them on the board.
+///
+/// The moves must be legal in turn, as for [`make_legal_move`].
+pub fn hash_after<BB: BitBoard, ZT: ZobristTables>(board: &BB, moves: &[ChessMove]) -> u64 {
     let mut res = HashOnly(
         board.curr_hash(),
         board.trans(),
         board.ply().0,
         board.castling(),
     );
+    for &mv in moves {
+        make_legal_move::<HashOnly, ZT>(&mut res, LegalMove(mv));
+    }
+    res.0
+}
+
+/// Compute the zobrist delta hash of a move purely from the move description
+/// and the transient state before the move, without needing a board.
+///
+/// XOR-ing the result onto the hash of the position the move is made in
+/// yields the hash of the resulting position.
+pub fn move_delta<ZT: ZobristTables>(
+    player: ChessColor,
+    mv: ChessMove,
+    trans: Transients,
+    castling: &'static BitCastling,
+) -> u64 {
+    let mut res = HashOnly(0, trans, player, castling);
     make_legal_move::<HashOnly, ZT>(&mut res, LegalMove(mv));
     res.0
 }
 
+/// Compute the squares which toggle on the total occupancy of the board
+/// when a move is made, from the move description alone.
+///
+/// XOR-ing the result onto the occupancy before the move yields the occupancy after it.
+pub fn occupancy_delta(mv: ChessMove, castling: &BitCastling) -> u64 {
+    let from = 1 << mv.from.ix();
+    let to = 1 << mv.to.ix();
+
+    if let Some(dir) = CastlingDirection::from_special(mv.special) {
+        let back_rank = 0xFF << (mv.from.ix() & 0x38);
+        return (castling.king_move[dir.ix()] ^ castling.rook_move[dir.ix()]) & back_rank;
+    }
+
+    match (mv.capture, mv.special) {
+        (Some(_), Some(SpecialMove::PAWN)) => from | to | 1 << (mv.to.ix() ^ 8),
+        (Some(_), _) => from,
+        (None, _) => from | to,
+    }
+}
+
+impl ChessMove {
+    /// Reconstruct a move of the active player from its starting and ending squares,
+    /// as given by e.g. UCI notation or a graphical interface.
+    ///
+    /// Castling, double pushes, and _en passant_ captures are inferred from the
+    /// board. Castling may be given either as the move of the king to its destination,
+    /// or as the king capturing its own rook while the right to castle is held, and is
+    /// written the way move generation writes it. Returns `None` if `from` is empty.
+    /// The move is not checked for legality.
+    pub fn from_squares<BB: BitBoard>(
+        board: &BB,
+        from: Square,
+        to: Square,
+        promotion: Option<PawnPromotion>,
+    ) -> Option<ChessMove> {
+        let ech = board.piece_at(from)?;
+        let player = board.ply().0;
+        let rules = &board.castling().rules;
+
+        let mut mv = ChessMove {
+            ech,
+            from,
+            to,
+            special: promotion.map(SpecialMove::from),
+            capture: board.commoner_at(to),
+        };
+
+        match ech {
+            ChessPiece::PAWN if from.ix().abs_diff(to.ix()) == 16 => {
+                mv.special = Some(SpecialMove::PAWN);
+            }
+            ChessPiece::PAWN
+                if mv.capture.is_none()
+                    && board.trans().en_passant.map(|ep| ep.target_square()) == Some(to) =>
+            {
+                mv.special = Some(SpecialMove::PAWN);
+                mv.capture = Some(ChessCommoner::PAWN);
+            }
+            ChessPiece::KING if from == rules.king_start[player.ix()] => {
+                let end = if rules.capture_own_rook {
+                    rules.rook_start[player.ix()]
+                } else {
+                    rules.king_end[player.ix()]
+                };
+                // The king taking its own rook is castling, as in Chess960 interfaces
+                let rooks = board.men(player, ChessPiece::ROOK);
+                let rights = board.trans().rights[player.ix()];
+                let takes_rook = |dir: CastlingDirection| {
+                    let rook = rules.rook_start[player.ix()][dir.ix()];
+                    rook == to && rooks & 1 << rook.ix() != 0 && rights[dir.ix()]
+                };
+
+                use CastlingDirection::*;
+                if let Some(dir) = [EAST, WEST]
+                    .into_iter()
+                    .find(|&dir| end[dir.ix()] == to || takes_rook(dir))
+                {
+                    mv.to = end[dir.ix()];
+                    mv.special = Some(SpecialMove::from(dir));
+                    mv.capture = None;
+                }
+            }
+            _ => {}
+        }
+
+        Some(mv)
+    }
+}
+
+#[test]
+fn from_squares_test() {
+    use crate::bitboard::{board::FullerBitBoard, hash::FullZobristTables, setup::parse_position};
+
+    let board = |fen| {
+        parse_position::<FullerBitBoard, FullZobristTables>(fen)
+            .unwrap()
+            .0
+    };
+
+    let start = board("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    assert_eq!(
+        ChessMove::from_squares(&start, Square::g1, Square::f3, None),
+        Some(ChessMove {
+            ech: ChessPiece::KNIGHT,
+            from: Square::g1,
+            to: Square::f3,
+            special: None,
+            capture: None,
+        })
+    );
+    assert_eq!(
+        ChessMove::from_squares(&start, Square::e2, Square::e4, None)
+            .unwrap()
+            .special,
+        Some(SpecialMove::PAWN)
+    );
+    assert_eq!(
+        ChessMove::from_squares(&start, Square::e4, Square::e5, None),
+        None
+    );
+
+    let capture = board("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2");
+    assert_eq!(
+        ChessMove::from_squares(&capture, Square::e4, Square::d5, None),
+        Some(ChessMove {
+            ech: ChessPiece::PAWN,
+            from: Square::e4,
+            to: Square::d5,
+            special: None,
+            capture: Some(ChessCommoner::PAWN),
+        })
+    );
+
+    let castle = board("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    assert_eq!(
+        ChessMove::from_squares(&castle, Square::e1, Square::g1, None),
+        Some(ChessMove {
+            ech: ChessPiece::KING,
+            from: Square::e1,
+            to: Square::g1,
+            special: Some(SpecialMove::from(CastlingDirection::WEST)),
+            capture: None,
+        })
+    );
+    assert_eq!(
+        ChessMove::from_squares(&castle, Square::e1, Square::f1, None)
+            .unwrap()
+            .special,
+        None
+    );
+
+    let kingside = ChessMove {
+        ech: ChessPiece::KING,
+        from: Square::e1,
+        to: Square::g1,
+        special: Some(SpecialMove::from(CastlingDirection::WEST)),
+        capture: None,
+    };
+    assert_eq!(
+        ChessMove::from_squares(&castle, Square::e1, Square::h1, None),
+        Some(kingside)
+    );
+    let no_rights = board("r3k2r/8/8/8/8/8/8/R3K2R w Qkq - 0 1");
+    assert_eq!(
+        ChessMove::from_squares(&no_rights, Square::e1, Square::h1, None)
+            .unwrap()
+            .special,
+        None
+    );
+
+    let en_passant = board("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+    assert_eq!(
+        ChessMove::from_squares(&en_passant, Square::e5, Square::f6, None),
+        Some(ChessMove {
+            ech: ChessPiece::PAWN,
+            from: Square::e5,
+            to: Square::f6,
+            special: Some(SpecialMove::PAWN),
+            capture: Some(ChessCommoner::PAWN),
+        })
+    );
+}
+
+#[test]
+fn from_squares_chess_960_test() {
+    use chumsky::Parser;
+
+    use crate::{
+        bitboard::{
+            attacking::FakeMoveSimplStrategy,
+            board::{FullerBitBoard, HasDefaultMetaBoard},
+            hash::FullZobristTables,
+            movegen::{LegalBlessing, enumerate},
+            setup::{apply_castling_rights, parse_position},
+            vision::MostlyBits,
+        },
+        notation::{CoordNotation, Parsable, fen::ColorCase},
+    };
+
+    static KING_MOVES: BitCastling = BitCastling::from_rules(CastlingRules {
+        capture_own_rook: false,
+        ..CHESS_960_RKNBBQNR
+    });
+    static TAKES_ROOK: BitCastling = BitCastling::from_rules(CHESS_960_RKNBBQNR);
+
+    let uci = CoordNotation::parser().parse("b1h1").into_result().unwrap();
+
+    for (castling, to) in [(&KING_MOVES, Square::g1), (&TAKES_ROOK, Square::h1)] {
+        let (mut board, _, _) =
+            parse_position::<FullerBitBoard, FullZobristTables>("rk5r/8/8/8/8/8/8/RK5R w - - 0 1")
+                .unwrap();
+        board.metaboard_mut().castling = castling;
+        apply_castling_rights::<_, FullZobristTables>(
+            &mut board,
+            &[ColorCase::White(CastlingDirection::WEST)],
+        );
+
+        let mv = ChessMove::from_squares(&board, uci.from, uci.to, uci.prom).unwrap();
+        assert_eq!(
+            mv,
+            ChessMove {
+                ech: ChessPiece::KING,
+                from: Square::b1,
+                to,
+                special: Some(SpecialMove::from(CastlingDirection::WEST)),
+                capture: None,
+            }
+        );
+
+        let mut moves = vec![];
+        enumerate::<_, MostlyBits, LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>>(
+            &board, &mut moves,
+        );
+        assert!(moves.contains(&LegalMove(mv)));
+    }
+}
+
+#[test]
+fn positions_test() {
+    use crate::{
+        bitboard::{board::FullerBitBoard, hash::FullZobristTables},
+        notation::fen::FenBoard,
+    };
+
+    type ZT = FullZobristTables;
+
+    let start = FullerBitBoard::startpos::<ZT>();
+    let moves = [
+        (Square::e2, Square::e4),
+        (Square::e7, Square::e5),
+        (Square::g1, Square::f3),
+        (Square::b8, Square::c6),
+    ]
+    .into_iter()
+    .scan(start.clone(), |board, (from, to)| {
+        let mv = ChessMove::from_squares(board, from, to, None)?;
+        make_legal_move::<_, ZT>(board, LegalMove(mv));
+        Some(mv)
+    })
+    .collect::<Vec<_>>();
+
+    let boards = positions::<_, ZT>(start, &moves).collect::<Vec<_>>();
+    assert_eq!(boards.len(), 4);
+    assert_eq!(
+        FenBoard::from_bitboard(&boards[0]).to_string(),
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    );
+    assert_eq!(
+        FenBoard::from_bitboard(boards.last().unwrap()).to_string(),
+        "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3"
+    );
+}
+
+#[test]
+fn occupancy_delta_test() {
+    use crate::bitboard::{board::FullerBitBoard, hash::FullZobristTables, setup::parse_position};
+
+    let check = |fen, from, to| {
+        let board = parse_position::<FullerBitBoard, FullZobristTables>(fen)
+            .unwrap()
+            .0;
+        let mv = ChessMove::from_squares(&board, from, to, None).unwrap();
+        let after = clone_make_legal_move::<_, FullZobristTables>(&board, LegalMove(mv));
+        let delta = occupancy_delta(mv, board.castling());
+        assert_eq!(board.total() ^ delta, after.total(), "{mv}");
+        delta.count_ones()
+    };
+
+    let startpos = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_eq!(check(startpos, Square::g1, Square::f3), 2);
+    assert_eq!(check(startpos, Square::e2, Square::e4), 2);
+
+    let capture = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2";
+    assert_eq!(check(capture, Square::e4, Square::d5), 1);
+
+    let castle = "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1";
+    assert_eq!(check(castle, Square::e8, Square::g8), 4);
+    assert_eq!(check(castle, Square::e8, Square::c8), 4);
+
+    let en_passant = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+    assert_eq!(check(en_passant, Square::e5, Square::f6), 3);
+}
+
+#[test]
+fn move_delta_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy,
+        board::FullerBitBoard,
+        hash::FullZobristTables,
+        movegen::{LegalBlessing, enumerate},
+        vision::MostlyBits,
+    };
+
+    type ZT = FullZobristTables;
+
+    let mut boards = vec![FullerBitBoard::startpos::<ZT>()];
+    let mut moves = vec![];
+
+    for _ in 0..3 {
+        let mut next = vec![];
+        for board in &boards {
+            enumerate::<_, MostlyBits, LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>>(
+                board, &mut moves,
+            );
+            for mv in moves.iter().copied() {
+                let delta = move_delta::<ZT>(board.ply().0, mv.0, board.trans(), board.castling());
+                let after = clone_make_legal_move::<_, ZT>(board, mv);
+                assert_eq!(board.curr_hash() ^ delta, after.curr_hash(), "{:?}", mv);
+                next.push(after);
+            }
+        }
+        boards = next;
+    }
+}
+
+#[test]
+fn hash_after_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+        movegen::LegalBlessing, setup::parse_position, status::random_legal_move,
+        vision::MostlyBits,
+    };
+    use rand::{SeedableRng, rngs::SmallRng};
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+    type ZT = FullZobristTables;
+
+    let (start, _, _) = parse_position::<FullerBitBoard, ZT>(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    )
+    .unwrap();
+
+    for seed in 0..20 {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut board = start.clone();
+        let mut moves = vec![];
+
+        while let Some(mv) = random_legal_move::<_, MostlyBits, Legal>(&board, &mut rng) {
+            board = clone_make_legal_move::<_, ZT>(&board, mv);
+            moves.push(mv.0);
+            assert_eq!(
+                hash_after::<_, ZT>(&start, &moves),
+                board.curr_hash(),
+                "{moves:?}"
+            );
+
+            if moves.len() == 40 {
+                break;
+            }
+        }
+    }
+}
+
 /// Make a simple move on a chessboard:
 ///
 /// - A chessman moves from one square to another
 /// - Optionally captures a chessman of the opposing color at its destination
-/// - A forefit of castling rights may occur if a rook moves from its starting square
+///
+/// Also advances the half-move clock for all moves, resetting it for captures and pawn moves,
+/// see [`ChessMove::resets_halfmove_clock`]
 #[inline]
 pub fn simple_move<BB: BitBoard, ZT: ZobristTables>(
     board: &mut BB,
@@ -119,7 +537,11 @@ pub fn simple_move<BB: BitBoard, ZT: ZobristTables>(
 ) {
     let player = board.ply().0;
 
-    board.set_halfmove_clock(board.trans().halfmove_clock + 1);
+    if mv.resets_halfmove_clock() {
+        board.set_halfmove_clock(0);
+    } else {
+        board.set_halfmove_clock(board.trans().halfmove_clock + 1);
+    }
 
     if mv.special.is_some() {
         return;
@@ -129,46 +551,182 @@ pub fn simple_move<BB: BitBoard, ZT: ZobristTables>(
 
     board.xor(player, mv.ech, bits);
 
-    rook_rights_loss(board, mv.ech, player, mv.from, zobristhashes);
     capturing_move(board, mv, mv.to, zobristhashes);
 
     board.hash(zobristhashes.hash_move(player, mv.ech, bits));
 }
 
-/// The loss of rook rights, either from having a rook captured, or moving it from its starting square.
+/// The castling rights which a move forfeits:
+///
+/// - A king move, including castling, forfeits both rights of its color
+/// - A rook moving from its starting square forfeits the right of that side
+/// - Capturing a rook on its starting square forfeits the right of the opponent on that side
+///
+/// Only rights still held are reported.
+pub fn rights_removed(board: &impl BitBoard, mv: ChessMove) -> [[bool; 2]; 2] {
+    use CastlingDirection::*;
+
+    let player = board.ply().0;
+    let rules = &board.castling().rules;
+    let mut res = [[false; 2]; 2];
+
+    if mv.ech == ChessPiece::KING {
+        res[player.ix()] = [true; 2];
+    }
+
+    for dir in [EAST, WEST] {
+        if mv.ech == ChessPiece::ROOK && mv.from == rules.rook_start[player.ix()][dir.ix()] {
+            res[player.ix()][dir.ix()] = true;
+        }
+
+        if mv.capture == Some(ChessCommoner::ROOK)
+            && mv.to == rules.rook_start[player.opp().ix()][dir.ix()]
+        {
+            res[player.opp().ix()][dir.ix()] = true;
+        }
+    }
+
+    let rights = board.trans().rights;
+    for c in [ChessColor::WHITE, ChessColor::BLACK] {
+        for dir in [EAST, WEST] {
+            res[c.ix()][dir.ix()] &= rights[c.ix()][dir.ix()];
+        }
+    }
+
+    res
+}
+
+/// The loss of castling rights, see [`rights_removed`].
 #[inline]
-pub fn rook_rights_loss<BB: BitBoard, ZT: ZobristTables>(
+pub fn rights_loss<BB: BitBoard, ZT: ZobristTables>(
     board: &mut BB,
-    piece: ChessPiece,
-    color: ChessColor,
-    sq: Square,
+    mv: ChessMove,
     zobristhashes: &'static ZT,
 ) {
-    use CastlingDirection::*;
-    if piece != ChessPiece::ROOK {
+    let removed = rights_removed(board, mv);
+
+    if removed == [[false; 2]; 2] {
         return;
     }
 
-    let castling = board.castling();
-
-    for dir in [EAST, WEST] {
-        if sq == castling.rules.rook_start[color.ix()][dir.ix()] {
-            let mut rights = board.trans().rights;
-
-            board.hash(zobristhashes.hash_rights(rights));
+    let mut rights = board.trans().rights;
 
-            rights[color.ix()][dir.ix()] = false;
+    board.hash(zobristhashes.hash_rights(rights));
 
-            board.set_castling_rights(rights);
-            board.hash(zobristhashes.hash_rights(rights));
+    for c in [ChessColor::WHITE, ChessColor::BLACK] {
+        for dir in [CastlingDirection::EAST, CastlingDirection::WEST] {
+            rights[c.ix()][dir.ix()] &= !removed[c.ix()][dir.ix()];
         }
     }
+
+    board.set_castling_rights(rights);
+    board.hash(zobristhashes.hash_rights(rights));
+}
+
+#[test]
+fn rights_never_return_test() {
+    use crate::{
+        bitboard::{
+            attacking::FakeMoveSimplStrategy,
+            board::{FullerBitBoard, MetaBoard},
+            hash::FullZobristTables,
+            movegen::LegalBlessing,
+            san::{play_sans, resolve_san},
+            setup::parse_position,
+            vision::MostlyBits,
+        },
+        notation::fen::FenBoard,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+    type ZT = FullZobristTables;
+
+    let (start, _, _) =
+        parse_position::<FullerBitBoard, ZT>("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+
+    let board = play_sans::<_, MostlyBits, Legal, ZT>(start.clone(), &["Ra2", "Kd8", "Ra1", "Ke8"])
+        .unwrap();
+    assert_eq!(
+        FenBoard::from_bitboard(&board).to_string(),
+        "4k3/8/8/8/8/8/8/R3K2R w K - 4 3"
+    );
+    assert_eq!(board.curr_hash(), board.rehash::<ZT>());
+
+    let mut board = start.clone();
+    let mv = resolve_san::<_, MostlyBits, Legal>(&board, "Ra2").unwrap();
+    let trans = make_legal_move::<_, ZT>(&mut board, mv);
+    assert!(!board.trans().rights[ChessColor::WHITE.ix()][CastlingDirection::EAST.ix()]);
+    unmake_legal_move::<_, ZT>(&mut board, mv, trans);
+    assert_eq!(board.trans().rights, start.trans().rights);
+    assert_eq!(board.curr_hash(), start.curr_hash());
+}
+
+#[test]
+fn en_passant_squares_test() {
+    use crate::bitboard::{board::FullerBitBoard, hash::FullZobristTables};
+
+    let board = FullerBitBoard::startpos::<FullZobristTables>();
+    let push = ChessMove {
+        ech: ChessPiece::PAWN,
+        from: Square::d2,
+        to: Square::d4,
+        special: Some(SpecialMove::PAWN),
+        capture: None,
+    };
+    let after = clone_make_legal_move::<_, FullZobristTables>(&board, LegalMove(push));
+
+    let eps = after.trans().en_passant.unwrap();
+    assert_eq!(eps.target_square(), Square::d3);
+    assert_eq!(eps.captured_square(), Square::d4);
+}
+
+#[test]
+fn rights_removed_test() {
+    use crate::bitboard::{board::FullerBitBoard, hash::FullZobristTables};
+
+    let mut board = FullerBitBoard::startpos::<FullZobristTables>();
+    board.xor(ChessColor::WHITE, ChessPiece::PAWN, 1 << Square::a2.ix());
+
+    let rook = ChessMove {
+        ech: ChessPiece::ROOK,
+        from: Square::a1,
+        to: Square::a3,
+        special: None,
+        capture: None,
+    };
+    let mut white_queenside = [[false; 2]; 2];
+    white_queenside[ChessColor::WHITE.ix()][CastlingDirection::EAST.ix()] = true;
+    assert_eq!(rights_removed(&board, rook), white_queenside);
+
+    let king = ChessMove {
+        ech: ChessPiece::KING,
+        from: Square::e1,
+        to: Square::e2,
+        ..rook
+    };
+    let mut white = [[false; 2]; 2];
+    white[ChessColor::WHITE.ix()] = [true; 2];
+    assert_eq!(rights_removed(&board, king), white);
+
+    let knight = ChessMove {
+        ech: ChessPiece::KNIGHT,
+        from: Square::g1,
+        to: Square::f3,
+        ..rook
+    };
+    assert_eq!(rights_removed(&board, knight), [[false; 2]; 2]);
+
+    let after = clone_make_legal_move::<_, FullZobristTables>(&board, LegalMove(rook));
+    assert_eq!(
+        after.trans().rights,
+        [[false, true], [true, true]],
+        "only white queenside is lost"
+    );
 }
 
 /// A capturing move:
 ///
 /// - A chessman of opposing color disappears from the board
-/// - A loss of castling rights occur if the captured piece is a rook on its starting square
 #[inline]
 pub fn capturing_move<BB: BitBoard, ZT: ZobristTables>(
     board: &mut BB,
@@ -184,11 +742,8 @@ pub fn capturing_move<BB: BitBoard, ZT: ZobristTables>(
 
     let man = ChessPiece::from(man);
 
-    board.set_halfmove_clock(0);
     board.xor(opponent, man, 1 << sq.ix());
 
-    rook_rights_loss(board, man, opponent, mv.to, zobristhashes);
-
     board.hash(zobristhashes.hash_square(opponent, man, sq));
 }
 
@@ -209,9 +764,6 @@ pub fn pawn_special<BB: BitBoard, ZT: ZobristTables>(
     let en_passant = board.trans().en_passant;
 
     board.set_en_passant(None);
-    if mv.ech == ChessPiece::PAWN {
-        board.set_halfmove_clock(0);
-    }
 
     board.hash(zobristhashes.hash_en_passant(en_passant));
 
@@ -272,9 +824,8 @@ pub fn promotion_move<BB: BitBoard, ZT: ZobristTables>(
 ///
 /// - The king and rook both move
 /// - No captures occur
-/// - Expenditure of castling rights
 ///
-/// This function also forefeits castling rights if the king moves in general.
+/// The expenditure of castling rights is handled by [`rights_loss`].
 #[inline]
 pub fn castling_move<BB: BitBoard, ZT: ZobristTables>(
     board: &mut BB,
@@ -291,18 +842,8 @@ pub fn castling_move<BB: BitBoard, ZT: ZobristTables>(
     let king_move = board.castling().king_move[castle.ix()] & back_rank;
     let rook_move = board.castling().rook_move[castle.ix()] & back_rank;
 
-    let mut rights = board.trans().rights;
-
-    board.hash(zobristhashes.hash_rights(rights));
-
-    rights[player.ix()] = [false; 2];
-
-    board.hash(zobristhashes.hash_rights(rights));
-
-    board.set_halfmove_clock(board.trans().halfmove_clock + 1);
     board.xor(player, ChessPiece::KING, king_move);
     board.xor(player, ChessPiece::ROOK, rook_move);
-    board.set_castling_rights(rights);
 
     board.hash(zobristhashes.hash_castling(player, king_move, rook_move));
 }
@@ -422,7 +963,8 @@ impl<'a, BB: BitBoard> BitBoard for MoveOnly<'a, BB> {
     }
 }
 
-/// An empty [`BitBoard`]-type which only hashes, without updating metadata or moving.
+/// An empty [`BitBoard`]-type which only hashes and keeps track of the transients
+/// and the active player, without moving.
 #[derive(Debug, Clone, Copy)]
 pub struct HashOnly(
     pub u64,
@@ -453,10 +995,14 @@ impl MetaBoard for HashOnly {
     }
 
     #[inline]
-    fn next_ply(&mut self) {}
+    fn next_ply(&mut self) {
+        self.2 = self.2.opp();
+    }
 
     #[inline]
-    fn prev_ply(&mut self) {}
+    fn prev_ply(&mut self) {
+        self.2 = self.2.opp();
+    }
 
     #[inline]
     fn castling(&self) -> &'static super::castling::BitCastling {
@@ -464,16 +1010,24 @@ impl MetaBoard for HashOnly {
     }
 
     #[inline]
-    fn set_halfmove_clock(&mut self, val: u8) {}
+    fn set_halfmove_clock(&mut self, val: u8) {
+        self.1.halfmove_clock = val;
+    }
 
     #[inline]
-    fn set_castling_rights(&mut self, rights: [[bool; 2]; 2]) {}
+    fn set_castling_rights(&mut self, rights: [[bool; 2]; 2]) {
+        self.1.rights = rights;
+    }
 
     #[inline]
-    fn set_en_passant(&mut self, rights: Option<EnPassant>) {}
+    fn set_en_passant(&mut self, rights: Option<EnPassant>) {
+        self.1.en_passant = rights;
+    }
 
     #[inline]
-    fn set_transients(&mut self, trans: Transients) {}
+    fn set_transients(&mut self, trans: Transients) {
+        self.1 = trans;
+    }
 }
 
 impl ChessBoard for HashOnly {
@@ -540,3 +1094,42 @@ impl BitBoard for HashOnly {
         self.piece_at(sq).and_then(ChessCommoner::from_piece)
     }
 }
+
+#[test]
+fn halfmove_clock_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy,
+        board::{CompactBitBoard, FullBitBoard, FullerBitBoard},
+        hash::FullZobristTables,
+        movegen::LegalBlessing,
+        san::resolve_san,
+        setup::parse_position,
+        vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+    type ZT = FullZobristTables;
+
+    fn check<BB: BitBoard>() {
+        let (board, _, _) =
+            parse_position::<BB, ZT>("r3k2r/p6p/8/3p4/8/2N5/P6P/R3K2R w KQkq - 5 20").unwrap();
+
+        for (san, clock) in [
+            ("O-O", 6),
+            ("O-O-O", 6),
+            ("Kf1", 6),
+            ("Nb5", 6),
+            ("Nxd5", 0),
+            ("a3", 0),
+            ("a4", 0),
+        ] {
+            let mv = resolve_san::<_, MostlyBits, Legal>(&board, san).unwrap();
+            let after = clone_make_legal_move::<_, ZT>(&board, mv);
+            assert_eq!(after.trans().halfmove_clock, clock, "{san}");
+        }
+    }
+
+    check::<CompactBitBoard>();
+    check::<FullBitBoard>();
+    check::<FullerBitBoard>();
+}
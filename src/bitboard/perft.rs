@@ -1,5 +1,6 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    cell::Cell,
+    collections::{BTreeMap, HashMap, HashSet},
     ops::{Deref, DerefMut},
     time::{Duration, Instant},
 };
@@ -8,15 +9,23 @@ use rand::{RngCore, rngs::SmallRng};
 
 use crate::{
     bitboard::{
-        board::BitBoard,
-        hash::{ZobHasher, ZobristTables, pi_rng},
-        movegen::{BlessingStrategy, enumerate},
+        attacking::{FakeMoveSimplStrategy, king_in_check},
+        board::{BitBoard, CompactBitBoard, FullBitBoard, FullerBitBoard},
+        hash::{
+            CompactZobristTables, FullZobristTables, NoHashes, ZobHasher, ZobristTables, pi_rng,
+        },
+        movegen::{BlessingStrategy, LegalBlessing, enumerate},
         moving::{clone_make_legal_move, make_legal_move, unmake_legal_move},
+        setup::parse_position,
+        status::{GameStatus, game_status},
         utils::SliceExtensions,
-        vision::Panopticon,
+        vision::{MostlyBits, Panopticon},
+    },
+    model::{CastlingDirection, LegalMove, PawnPromotion, SpecialMove, Transients},
+    notation::{
+        CoordNotation,
+        fen::{FenBoard, FenError},
     },
-    model::{LegalMove, Transients},
-    notation::CoordNotation,
 };
 
 pub fn perft<
@@ -28,13 +37,33 @@ pub fn perft<
 >(
     depth: usize,
     bulk: bool,
+    by_depth: bool,
+    memoizer: impl PerftMemoizer,
+) -> PerfTestRes {
+    perft_from::<BB, X, L, RC, ZT>(BB::startpos::<ZT>(), depth, bulk, by_depth, memoizer)
+}
+
+/// Like [`perft`], but from the given position rather than the starting position.
+pub fn perft_from<
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    RC: RecursionStrategy,
+    ZT: ZobristTables,
+>(
+    mut startpos: BB,
+    depth: usize,
+    bulk: bool,
+    by_depth: bool,
     mut memoizer: impl PerftMemoizer,
 ) -> PerfTestRes {
     let mut breakdown = BTreeMap::new();
+    let mut nodes = if by_depth { vec![0; depth + 1] } else { vec![] };
     let now = Instant::now();
 
+    count_nodes(&mut nodes, depth, 1);
+
     let mut firstmoves = vec![];
-    let mut startpos = BB::startpos::<ZT>();
 
     if depth != 0 {
         enumerate::<BB, X, L>(&startpos, &mut firstmoves);
@@ -42,6 +71,7 @@ pub fn perft<
         if depth == 1 {
             for mv in firstmoves {
                 let rec = RC::recurse::<BB, ZT>(&mut startpos, mv);
+                count_nodes(&mut nodes, 0, 1);
                 breakdown.insert(CoordNotation::from(mv.0), 1);
                 RC::reclaim::<BB, ZT>(rec);
             }
@@ -58,6 +88,7 @@ pub fn perft<
                         &buf[..],
                         bulk,
                         &mut memoizer,
+                        &mut nodes,
                     ),
                 );
                 RC::reclaim::<BB, ZT>(rec);
@@ -65,11 +96,103 @@ pub fn perft<
         }
     }
 
+    nodes.reverse();
+
     PerfTestRes {
         elapsed_duration: now.elapsed(),
         breakdown,
         depth,
         memo_used: memoizer.size(),
+        memo_stats: memoizer.stats(),
+        nodes_by_depth: by_depth.then_some(nodes),
+    }
+}
+
+/// Performance test of a position given in FEN, using the fastest
+/// board representation and strategies of this crate.
+pub fn perft_fen(fen: &str, depth: usize) -> Result<PerfTestRes, FenError> {
+    let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen)?;
+    Ok(perft_from::<
+        FullerBitBoard,
+        MostlyBits,
+        LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>,
+        MakeUnmake,
+        FullZobristTables,
+    >(board, depth, true, false, ()))
+}
+
+/// Find where move generation disagrees with a reference perft from the starting position.
+///
+/// See [`bisect_perft_from`].
+pub fn bisect_perft<
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    RC: RecursionStrategy,
+    ZT: ZobristTables,
+>(
+    depth: usize,
+    reference: impl Fn(&str, usize) -> usize,
+) -> Option<Vec<CoordNotation>> {
+    bisect_perft_from::<BB, X, L, RC, ZT>(BB::startpos::<ZT>(), depth, reference)
+}
+
+/// Find where move generation disagrees with a reference perft, such as
+/// a trusted engine, given the FEN of a position and a depth.
+///
+/// Descends into the first move whose subtree count disagrees with the reference,
+/// returning the moves leading to the deepest position whose count is wrong
+/// while the counts of all its moves are right, that is, the position whose
+/// moves are wrongly generated. Returns `None` if the counts agree.
+pub fn bisect_perft_from<
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    RC: RecursionStrategy,
+    ZT: ZobristTables,
+>(
+    board: BB,
+    depth: usize,
+    reference: impl Fn(&str, usize) -> usize,
+) -> Option<Vec<CoordNotation>> {
+    let count = |board: &BB, depth| {
+        perft_from::<BB, X, L, RC, ZT>(board.clone(), depth, true, false, ()).nodes()
+    };
+    let fen = |board: &BB| FenBoard::from_bitboard(board).to_string();
+
+    if depth == 0 || count(&board, depth) == reference(&fen(&board), depth) {
+        return None;
+    }
+
+    let mut line = vec![];
+    let mut board = board;
+    let mut moves = vec![];
+
+    // The leaves of a position at depth 1 cannot disagree
+    for depth in (2..=depth).rev() {
+        enumerate::<BB, X, L>(&board, &mut moves);
+
+        let diverging = moves.iter().find_map(|&mv| {
+            let next = clone_make_legal_move::<BB, ZT>(&board, mv);
+            (count(&next, depth - 1) != reference(&fen(&next), depth - 1)).then_some((mv, next))
+        });
+
+        let Some((mv, next)) = diverging else {
+            break;
+        };
+
+        line.push(CoordNotation::from(mv.0));
+        board = next;
+    }
+
+    Some(line)
+}
+
+/// Tally nodes with `depth` plies remaining, if node counting is enabled.
+#[inline]
+fn count_nodes(nodes: &mut [usize], depth: usize, n: usize) {
+    if let Some(c) = nodes.get_mut(depth) {
+        *c += n;
     }
 }
 
@@ -85,21 +208,26 @@ fn perft_recurse<
     moves: &[LegalMove],
     bulk: bool,
     mut memoizer: &mut impl PerftMemoizer,
+    nodes: &mut [usize],
 ) -> usize {
     if let Some(n) = memoizer.remember(board.curr_hash(), depth) {
         return n;
     }
 
+    count_nodes(nodes, depth, 1);
+
     let mut res = 0;
     if depth == 0 {
         res += 1;
     } else if depth == 1 {
         if bulk {
-            res += moves.len()
+            res += moves.len();
+            count_nodes(nodes, 0, moves.len());
         } else {
             for mv in moves.clones() {
                 let rec = RC::recurse::<BB, ZT>(board, mv);
                 res += 1;
+                count_nodes(nodes, 0, 1);
                 RC::reclaim::<BB, ZT>(rec);
             }
         }
@@ -117,6 +245,7 @@ fn perft_recurse<
                     &buf[..],
                     bulk,
                     memoizer,
+                    nodes,
                 );
                 memoizer.memoize(rec.curr_hash(), depth - 1, n);
                 res += n;
@@ -130,14 +259,207 @@ fn perft_recurse<
     res
 }
 
+/// Run the performance test on every combination of board
+/// representation and Zobrist table, for profiling them against each other.
+pub fn benchmark_results(depth: usize) -> Vec<(String, PerfTestRes)> {
+    let mut res = vec![];
+    benchmark_hashes::<CompactBitBoard>("Compact", depth, &mut res);
+    benchmark_hashes::<FullBitBoard>("Full", depth, &mut res);
+    benchmark_hashes::<FullerBitBoard>("Fuller", depth, &mut res);
+    res
+}
+
+/// Nodes per second of each combination in [`benchmark_results`].
+pub fn benchmark_all(depth: usize) -> Vec<(String, f64)> {
+    benchmark_results(depth)
+        .into_iter()
+        .map(|(name, res)| (name, res.nodes_per_second()))
+        .collect()
+}
+
+/// Perft from the starting position with [`MakeUnmake`] and [`CloneMake`] in turn.
+pub fn recursion_results(depth: usize) -> (PerfTestRes, PerfTestRes) {
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+    (
+        perft::<FullerBitBoard, MostlyBits, Legal, MakeUnmake, FullZobristTables>(
+            depth,
+            false,
+            false,
+            (),
+        ),
+        perft::<FullerBitBoard, MostlyBits, Legal, CloneMake, FullZobristTables>(
+            depth,
+            false,
+            false,
+            (),
+        ),
+    )
+}
+
+/// Elapsed time of [`MakeUnmake`] and [`CloneMake`] in [`recursion_results`].
+pub fn compare_recursion(depth: usize) -> (Duration, Duration) {
+    let (make_unmake, clone_make) = recursion_results(depth);
+    (make_unmake.elapsed_duration, clone_make.elapsed_duration)
+}
+
+fn benchmark_hashes<BB: BitBoard>(board: &str, depth: usize, res: &mut Vec<(String, PerfTestRes)>) {
+    res.push((
+        format!("{board}/Compact"),
+        benchmark::<BB, CompactZobristTables>(depth),
+    ));
+    res.push((
+        format!("{board}/Full"),
+        benchmark::<BB, FullZobristTables>(depth),
+    ));
+    res.push((format!("{board}/None"), benchmark::<BB, NoHashes>(depth)));
+}
+
+fn benchmark<BB: BitBoard, ZT: ZobristTables>(depth: usize) -> PerfTestRes {
+    perft::<BB, MostlyBits, LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>, CloneMake, ZT>(
+        depth,
+        false,
+        false,
+        (),
+    )
+}
+
+/// Tallies of the kinds of moves made at the leaves of a performance test,
+/// as given in published perft tables.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerftTypes {
+    pub nodes: usize,
+    pub captures: usize,
+    pub en_passant: usize,
+    pub castles: usize,
+    pub promotions: usize,
+    pub checks: usize,
+    pub checkmates: usize,
+}
+
+/// Performance test from the given position, breaking the leaf nodes down by move type.
+///
+/// Unlike [`perft`] this makes no use of bulk counting or memoization,
+/// as every leaf move has to be made to detect check.
+pub fn perft_types<BB, X, L, ZT>(board: &BB, depth: usize) -> PerftTypes
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    ZT: ZobristTables,
+{
+    let mut types = PerftTypes::default();
+    if depth == 0 {
+        types.nodes = 1;
+    } else {
+        perft_types_recurse::<BB, X, L, ZT>(board, depth, &mut types);
+    }
+    types
+}
+
+fn perft_types_recurse<BB, X, L, ZT>(board: &BB, depth: usize, types: &mut PerftTypes)
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    ZT: ZobristTables,
+{
+    let mut moves = vec![];
+    enumerate::<BB, X, L>(board, &mut moves);
+
+    for mv in moves {
+        let next = clone_make_legal_move::<BB, ZT>(board, mv);
+
+        if depth > 1 {
+            perft_types_recurse::<BB, X, L, ZT>(&next, depth - 1, types);
+            continue;
+        }
+
+        let LegalMove(mv) = mv;
+        types.nodes += 1;
+        types.captures += mv.capture.is_some() as usize;
+        types.en_passant +=
+            (mv.capture.is_some() && mv.special == Some(SpecialMove::PAWN)) as usize;
+        types.castles += CastlingDirection::from_special(mv.special).is_some() as usize;
+        types.promotions += PawnPromotion::from_special(mv.special).is_some() as usize;
+
+        if king_in_check::<BB, X>(&next, next.ply().0) {
+            types.checks += 1;
+            if game_status::<BB, X, L>(&next) == GameStatus::Checkmate {
+                types.checkmates += 1;
+            }
+        }
+    }
+}
+
+/// The leaf nodes of a performance test, along with the sum of the number
+/// of legal moves at every node of the tree, leaves included.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerftMobility {
+    pub nodes: usize,
+    pub mobility: usize,
+}
+
+/// Performance test from the given position, also summing the mobility of
+/// every node, as an independent quantity to cross-check move generation.
+///
+/// Like [`perft_types`] this makes no use of bulk counting or memoization,
+/// as the moves of every leaf have to be enumerated.
+pub fn perft_mobility<BB, X, L, ZT>(board: &BB, depth: usize) -> PerftMobility
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    ZT: ZobristTables,
+{
+    let mut res = PerftMobility::default();
+    perft_mobility_recurse::<BB, X, L, ZT>(board, depth, &mut res);
+    res
+}
+
+fn perft_mobility_recurse<BB, X, L, ZT>(board: &BB, depth: usize, res: &mut PerftMobility)
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    ZT: ZobristTables,
+{
+    let mut moves = vec![];
+    enumerate::<BB, X, L>(board, &mut moves);
+    res.mobility += moves.len();
+
+    if depth == 0 {
+        res.nodes += 1;
+        return;
+    }
+
+    for mv in moves {
+        let next = clone_make_legal_move::<BB, ZT>(board, mv);
+        perft_mobility_recurse::<BB, X, L, ZT>(&next, depth - 1, res);
+    }
+}
+
 pub struct PerfTestRes {
     pub depth: usize,
     pub elapsed_duration: Duration,
     pub breakdown: BTreeMap<CoordNotation, usize>,
     pub memo_used: (usize, usize),
+    pub memo_stats: MemoStats,
+    /// Number of nodes visited at each ply from the root, if requested.
+    ///
+    /// Nodes whose count was remembered by the memoizer are not visited,
+    /// and neither are their descendants.
+    pub nodes_by_depth: Option<Vec<usize>>,
 }
 
 impl PerfTestRes {
+    pub fn nodes(&self) -> usize {
+        self.breakdown.values().sum()
+    }
+
+    pub fn nodes_per_second(&self) -> f64 {
+        self.nodes() as f64 / self.elapsed_duration.as_secs_f64()
+    }
+
     pub fn pretty_print(&self) {
         println!("Performance test depth {}", self.depth);
         for (mv, n) in &self.breakdown {
@@ -147,12 +469,17 @@ impl PerfTestRes {
             "Time elapsed: {:.02}ms",
             self.elapsed_duration.as_millis_f64()
         );
+        println!("Nodes per second: {:.02}", self.nodes_per_second());
+        println!("Memorization: {}/{}", self.memo_used.0, self.memo_used.1);
         println!(
-            "Nodes per second: {:.02}",
-            self.breakdown.values().sum::<usize>() as f64 / self.elapsed_duration.as_secs_f64()
+            "Memo hit rate: {:.02}% of {} lookups",
+            self.memo_stats.hit_rate() * 100.0,
+            self.memo_stats.hits + self.memo_stats.misses
         );
-        println!("Memorization: {}/{}", self.memo_used.0, self.memo_used.1);
-        println!("Nodes searched: {}", self.breakdown.values().sum::<usize>());
+        if let Some(nodes) = &self.nodes_by_depth {
+            println!("Nodes by depth: {:?}", nodes);
+        }
+        println!("Nodes searched: {}", self.nodes());
     }
 }
 
@@ -160,6 +487,29 @@ pub trait PerftMemoizer {
     fn memoize(&mut self, key: u64, depth: usize, value: usize);
     fn remember(&self, key: u64, depth: usize) -> Option<usize>;
     fn size(&self) -> (usize, usize);
+    fn stats(&self) -> MemoStats;
+}
+
+/// How effective a memoizer has been.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    /// Lookups which found a value.
+    pub hits: usize,
+    /// Lookups which found nothing.
+    pub misses: usize,
+    /// Values memoized.
+    pub stores: usize,
+}
+
+impl MemoStats {
+    /// The fraction of lookups which found a value.
+    pub fn hit_rate(&self) -> f64 {
+        if self.hits + self.misses == 0 {
+            0.0
+        } else {
+            self.hits as f64 / (self.hits + self.misses) as f64
+        }
+    }
 }
 
 impl PerftMemoizer for () {
@@ -174,9 +524,39 @@ impl PerftMemoizer for () {
     fn size(&self) -> (usize, usize) {
         (0, 0)
     }
+
+    fn stats(&self) -> MemoStats {
+        MemoStats::default()
+    }
 }
 
-pub struct HashMapMemo(HashMap<(u64, u64), usize, ZobHasher>, Vec<u64>, SmallRng);
+/// Borrow a memoizer, to keep its values after the performance test.
+impl<M: PerftMemoizer> PerftMemoizer for &mut M {
+    #[inline]
+    fn memoize(&mut self, key: u64, depth: usize, value: usize) {
+        (**self).memoize(key, depth, value)
+    }
+
+    #[inline]
+    fn remember(&self, key: u64, depth: usize) -> Option<usize> {
+        (**self).remember(key, depth)
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (**self).size()
+    }
+
+    fn stats(&self) -> MemoStats {
+        (**self).stats()
+    }
+}
+
+pub struct HashMapMemo(
+    HashMap<(u64, u64), usize, ZobHasher>,
+    Vec<u64>,
+    SmallRng,
+    Cell<MemoStats>,
+);
 
 impl HashMapMemo {
     pub fn new(depth: usize) -> Self {
@@ -192,8 +572,55 @@ impl HashMapMemo {
             HashMap::with_capacity_and_hasher(10usize.pow(depth as u32), ZobHasher(0)),
             vec,
             rng,
+            Cell::default(),
         )
     }
+
+    /// Serialize the memoized values, to warm up a later run with [`HashMapMemo::load`].
+    ///
+    /// The entries are keyed by the per-depth salts, so these are saved as well.
+    /// The format is a sequence of little-endian `u64`: the number of salts,
+    /// the salts, and then triples of hash key, salt, and node count.
+    pub fn save(&self) -> Vec<u8> {
+        let mut words = vec![self.1.len() as u64];
+        words.extend(&self.1);
+        for (&(key, salt), &count) in &self.0 {
+            words.extend([key, salt, count as u64]);
+        }
+        words.into_iter().flat_map(u64::to_le_bytes).collect()
+    }
+
+    /// Deserialize memoized values saved by [`HashMapMemo::save`], or `None` if malformed.
+    pub fn load(bytes: &[u8]) -> Option<Self> {
+        if !bytes.len().is_multiple_of(8) {
+            return None;
+        }
+
+        let words = bytes
+            .chunks_exact(8)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        let (&depth, rest) = words.split_first()?;
+        let depth = usize::try_from(depth).ok().filter(|&d| d <= rest.len())?;
+        let (salts, entries) = rest.split_at(depth);
+        if !entries.len().is_multiple_of(3) {
+            return None;
+        }
+
+        // Continue the salt sequence where the saved table left off
+        let mut res = Self::new(0);
+        for _ in 0..depth {
+            res.2.next_u64();
+        }
+        res.1 = salts.to_vec();
+        res.0.extend(
+            entries
+                .chunks_exact(3)
+                .map(|e| ((e[0], e[1]), e[2] as usize)),
+        );
+
+        Some(res)
+    }
 }
 
 impl PerftMemoizer for HashMapMemo {
@@ -202,18 +629,33 @@ impl PerftMemoizer for HashMapMemo {
             self.1.push(self.2.next_u64())
         }
         self.0.insert((key, self.1[depth]), value);
+        self.3.update(|s| MemoStats {
+            stores: s.stores + 1,
+            ..s
+        });
     }
 
     fn remember(&self, key: u64, depth: usize) -> Option<usize> {
-        if depth >= self.1.len() {
-            return None;
-        }
-        self.0.get(&(key, self.1[depth])).map(|x| *x)
+        let res = if depth >= self.1.len() {
+            None
+        } else {
+            self.0.get(&(key, self.1[depth])).map(|x| *x)
+        };
+        self.3.update(|s| MemoStats {
+            hits: s.hits + res.is_some() as usize,
+            misses: s.misses + res.is_none() as usize,
+            ..s
+        });
+        res
     }
 
     fn size(&self) -> (usize, usize) {
         (self.0.len(), self.0.capacity())
     }
+
+    fn stats(&self) -> MemoStats {
+        self.3.get()
+    }
 }
 
 pub trait RecursionStrategy {
@@ -289,3 +731,316 @@ impl RecursionStrategy for CloneMake {
     #[inline]
     fn reclaim<'a, BB: BitBoard + 'a, ZT: ZobristTables>(claim: Self::Claim<'a, BB>) {}
 }
+
+#[test]
+fn perft_nodes_by_depth_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+        movegen::LegalBlessing, vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    for bulk in [false, true] {
+        let res = perft::<FullerBitBoard, MostlyBits, Legal, MakeUnmake, FullZobristTables>(
+            3,
+            bulk,
+            true,
+            (),
+        );
+        assert_eq!(res.nodes_by_depth, Some(vec![1, 20, 400, 8902]));
+    }
+
+    let res = perft::<FullerBitBoard, MostlyBits, Legal, MakeUnmake, FullZobristTables>(
+        3,
+        true,
+        false,
+        (),
+    );
+    assert_eq!(res.nodes_by_depth, None);
+}
+
+#[test]
+fn perft_types_test() {
+    use crate::{
+        bitboard::{
+            attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+            movegen::LegalBlessing, vision::MostlyBits,
+        },
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let types = |fen: &str, depth| {
+        let board = FenBoard::parser()
+            .parse(fen)
+            .into_result()
+            .unwrap()
+            .as_bitboard::<FullerBitBoard, FullZobristTables>();
+        perft_types::<_, MostlyBits, Legal, FullZobristTables>(&board, depth)
+    };
+
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    assert_eq!(
+        types(kiwipete, 2),
+        PerftTypes {
+            nodes: 2039,
+            captures: 351,
+            en_passant: 1,
+            castles: 91,
+            promotions: 0,
+            checks: 3,
+            checkmates: 0,
+        }
+    );
+
+    let position4 = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+    assert_eq!(
+        types(position4, 2),
+        PerftTypes {
+            nodes: 264,
+            captures: 87,
+            en_passant: 0,
+            castles: 6,
+            promotions: 48,
+            checks: 10,
+            checkmates: 0,
+        }
+    );
+}
+
+#[test]
+fn perft_mobility_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy,
+        board::{ChessBoard, FullerBitBoard},
+        hash::FullZobristTables,
+        movegen::LegalBlessing,
+        vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let board = FullerBitBoard::startpos::<FullZobristTables>();
+    let mobility = |depth| perft_mobility::<_, MostlyBits, Legal, FullZobristTables>(&board, depth);
+
+    // The mobility at depth n is the sum of the node counts of depths 1 through n + 1
+    assert_eq!(
+        mobility(0),
+        PerftMobility {
+            nodes: 1,
+            mobility: 20
+        }
+    );
+    assert_eq!(
+        mobility(2),
+        PerftMobility {
+            nodes: 400,
+            mobility: 20 + 400 + 8902
+        }
+    );
+    assert_eq!(mobility(3).mobility, 20 + 400 + 8902 + 197281);
+}
+
+#[test]
+fn perft_fen_test() {
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    let res = perft_fen(KIWIPETE, 3).unwrap();
+    assert_eq!(res.depth, 3);
+    assert_eq!(res.breakdown.len(), 48);
+    assert_eq!(res.nodes(), 97862);
+
+    assert!(perft_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq", 1).is_err());
+}
+
+#[test]
+fn perft_black_to_move_test() {
+    // After 1.e4, so that the attacks of black are needed
+    // to find the legal replies of white
+    const E4: &str = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+
+    for (depth, nodes) in [(1, 20), (2, 600), (3, 13160)] {
+        assert_eq!(perft_fen(E4, depth).unwrap().nodes(), nodes);
+    }
+}
+
+#[test]
+fn bisect_perft_test() {
+    use crate::{
+        bitboard::movegen::{LegalMoveBlesser, MoveBlesser},
+        model::ChessMove,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    /// Legal move generation with a simulated bug: en-passant captures are never generated.
+    struct NoEnPassant;
+    struct NoEnPassantBlesser<'a, BB: BitBoard>(
+        LegalMoveBlesser<'a, BB, FakeMoveSimplStrategy<MostlyBits>>,
+    );
+
+    impl BlessingStrategy for NoEnPassant {
+        type Blessing = LegalMove;
+        type Blesser<'a, BB: BitBoard + 'a> = NoEnPassantBlesser<'a, BB>;
+    }
+
+    impl<'a, BB: BitBoard> MoveBlesser<'a, BB> for NoEnPassantBlesser<'a, BB> {
+        type BlessedMove = LegalMove;
+
+        fn new(board: &'a BB) -> Self {
+            Self(MoveBlesser::new(board))
+        }
+
+        fn bless(&self, board: &'a BB, mv: ChessMove) -> Option<LegalMove> {
+            let en_passant = mv.capture.is_some() && mv.special == Some(SpecialMove::PAWN);
+            if en_passant {
+                None
+            } else {
+                self.0.bless(board, mv)
+            }
+        }
+    }
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let known = HashMap::from([((KIWIPETE, 3), 97862), ((KIWIPETE, 2), 2039)]);
+    let reference = |fen: &str, depth| match known.get(&(fen, depth)) {
+        Some(&n) => n,
+        None => perft_fen(fen, depth).unwrap().nodes(),
+    };
+
+    let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(KIWIPETE).unwrap();
+
+    let line = bisect_perft_from::<_, MostlyBits, Legal, MakeUnmake, FullZobristTables>(
+        board.clone(),
+        3,
+        reference,
+    );
+    assert_eq!(line, None);
+
+    let line = bisect_perft_from::<_, MostlyBits, NoEnPassant, MakeUnmake, FullZobristTables>(
+        board, 3, reference,
+    )
+    .unwrap();
+    // After a2a3 c7c5, white cannot capture d5xc6 en passant
+    assert_eq!(
+        line.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        ["a2a3", "c7c5"]
+    );
+
+    assert_eq!(
+        bisect_perft::<FullerBitBoard, MostlyBits, NoEnPassant, MakeUnmake, FullZobristTables>(
+            2,
+            |fen, depth| perft_fen(fen, depth).unwrap().nodes(),
+        ),
+        None
+    );
+}
+
+#[test]
+fn compare_recursion_test() {
+    let (make_unmake, clone_make) = recursion_results(4);
+    assert_eq!(make_unmake.nodes(), 197281);
+    assert_eq!(make_unmake.breakdown, clone_make.breakdown);
+
+    let (make_unmake, clone_make) = compare_recursion(2);
+    assert!(make_unmake > Duration::ZERO && clone_make > Duration::ZERO);
+}
+
+#[test]
+fn memo_save_load_test() {
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let mut memo = HashMapMemo::new(2);
+    let res = perft::<FullerBitBoard, MostlyBits, Legal, MakeUnmake, FullZobristTables>(
+        4, true, false, &mut memo,
+    );
+    assert_eq!(res.nodes(), 197281);
+    assert_eq!(memo.1.len(), 4);
+
+    let saved = memo.save();
+    let mut loaded = HashMapMemo::load(&saved).unwrap();
+    assert_eq!(loaded.0, memo.0);
+    assert_eq!(loaded.1, memo.1);
+    for &(key, _) in memo.0.keys() {
+        for depth in 0..5 {
+            assert_eq!(loaded.remember(key, depth), memo.remember(key, depth));
+        }
+    }
+
+    loaded.memoize(0, 6, 1);
+    memo.memoize(0, 6, 1);
+    assert_eq!(loaded.1, memo.1);
+
+    let res = perft::<FullerBitBoard, MostlyBits, Legal, MakeUnmake, FullZobristTables>(
+        4,
+        true,
+        false,
+        HashMapMemo::load(&saved).unwrap(),
+    );
+    assert_eq!(res.nodes(), 197281);
+
+    assert!(HashMapMemo::load(&saved[1..]).is_none());
+    assert!(HashMapMemo::load(&saved[..16]).is_none());
+    assert!(HashMapMemo::load(&[]).is_none());
+}
+
+#[test]
+fn benchmark_all_test() {
+    let res = benchmark_results(3);
+    assert_eq!(res.len(), 9);
+    assert!(res.iter().all(|(_, res)| res.nodes() == 8902));
+
+    let names = res
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<HashSet<_>>();
+    assert_eq!(names.len(), 9);
+
+    assert!(
+        benchmark_all(1)
+            .iter()
+            .all(|(_, nps)| nps.is_finite() && *nps > 0.0)
+    );
+}
+
+#[test]
+fn memo_stats_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+        movegen::LegalBlessing, vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let res = perft::<FullerBitBoard, MostlyBits, Legal, MakeUnmake, FullZobristTables>(
+        5,
+        true,
+        false,
+        HashMapMemo::new(5),
+    );
+
+    assert_eq!(res.nodes(), 4865609);
+    assert_eq!(
+        res.memo_stats,
+        MemoStats {
+            hits: 68456,
+            misses: 218504,
+            stores: 218504,
+        }
+    );
+    // Every store is of a fresh key or overwrites an old one
+    assert_eq!(res.memo_used.0, 109262);
+    assert!(res.memo_stats.stores >= res.memo_used.0);
+    assert_eq!(res.memo_stats.hit_rate(), 68456.0 / (68456 + 218504) as f64);
+
+    let res = perft::<FullerBitBoard, MostlyBits, Legal, MakeUnmake, FullZobristTables>(
+        3,
+        true,
+        false,
+        (),
+    );
+    assert_eq!(res.memo_stats, MemoStats::default());
+}
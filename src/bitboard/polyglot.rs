@@ -0,0 +1,265 @@
+//! Polyglot opening books.
+//!
+//! The Polyglot format is the de facto standard for opening books. Positions
+//! are keyed by a Zobrist-style hash of the position, the active player, the castling
+//! rights, and the _en passant_ file, but never the move counters.
+//!
+//! The hash uses a fixed array of 781 random values, named `Random64` in the
+//! Polyglot book format specification. This array is not bundled with the library,
+//! and must be supplied to [`polyglot_key`] by the caller.
+//!
+//! Book files are a sorted sequence of 16 byte entries, see [`probe_book`].
+
+use strum::VariantArray;
+
+use crate::{
+    bitboard::{
+        binary::{black_pawn_attack_fill, white_pawn_attack_fill},
+        board::BitBoard,
+        utils::SliceExtensions,
+    },
+    biterate,
+    notation::CoordNotation,
+};
+
+use crate::model::*;
+
+/// Number of random values used by the Polyglot hash.
+pub const POLYGLOT_RANDOMS: usize = 781;
+
+/// Offset of the castling rights values in the Polyglot random array.
+const CASTLE_OFFSET: usize = 768;
+/// Offset of the _en passant_ file values in the Polyglot random array.
+const EN_PASSANT_OFFSET: usize = 772;
+/// Offset of the white to move value in the Polyglot random array.
+const TURN_OFFSET: usize = 780;
+
+/// Key of a position for the purposes of this library's own opening books
+/// and transposition tables.
+///
+/// This is the Zobrist hash of the position, which covers the chessmen, the active
+/// player, the castling rights, and the _en passant_ state, but not the half-move
+/// clock or the turn number, so that transpositions share keys.
+pub fn book_key<BB: BitBoard>(board: &BB) -> u64 {
+    board.curr_hash()
+}
+
+/// Key of a position as used in Polyglot opening books, given the `Random64`
+/// array of the Polyglot book format specification.
+///
+/// Unlike [`book_key`], the _en passant_ file is only hashed if a pawn of the
+/// active player stands ready to capture, whether or not the capture is legal.
+pub fn polyglot_key<BB: BitBoard>(board: &BB, randoms: &[u64; POLYGLOT_RANDOMS]) -> u64 {
+    use CastlingDirection::*;
+    use ChessColor::*;
+
+    let mut key = 0;
+
+    for color in [WHITE, BLACK] {
+        for ech in ChessPiece::VARIANTS.clones() {
+            // Polyglot orders the kinds of chessmen black pawn, white pawn, black knight, ...
+            let kind = 2 * ech.ix() + color.opp().ix();
+            biterate! {for sq in board.men(color, ech); {
+                key ^= randoms[64 * kind + sq.ix()];
+            }}
+        }
+    }
+
+    let trans = board.trans();
+    for (ix, (color, dir)) in [(WHITE, WEST), (WHITE, EAST), (BLACK, WEST), (BLACK, EAST)]
+        .into_iter()
+        .enumerate()
+    {
+        if trans.rights[color.ix()][dir.ix()] {
+            key ^= randoms[CASTLE_OFFSET + ix];
+        }
+    }
+
+    let player = board.ply().0;
+
+    if let Some(ep) = trans.en_passant {
+        let square = 1 << ep.square.ix();
+        let capturers = match player {
+            WHITE => black_pawn_attack_fill(square),
+            BLACK => white_pawn_attack_fill(square),
+        };
+
+        if capturers & board.men(player, ChessPiece::PAWN) != 0 {
+            key ^= randoms[EN_PASSANT_OFFSET + ep.square.coords().0.ix()];
+        }
+    }
+
+    if player == WHITE {
+        key ^= randoms[TURN_OFFSET];
+    }
+
+    key
+}
+
+#[test]
+fn polyglot_key_test() {
+    use crate::{
+        bitboard::{board::FullerBitBoard, hash::FullZobristTables, hash::pi_rng},
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+    use rand::RngCore;
+
+    let mut rng = pi_rng();
+    let randoms = std::array::from_fn(|_| rng.next_u64());
+
+    let key = |fen: &str| {
+        let board = FenBoard::parser()
+            .parse(fen)
+            .into_result()
+            .unwrap()
+            .as_bitboard::<FullerBitBoard, FullZobristTables>();
+        polyglot_key(&board, &randoms)
+    };
+
+    let white_pawn = |sq: Square| randoms[64 + sq.ix()];
+
+    // 1.e4: the pawn moves and the turn passes, but no black pawn can capture on e3
+    assert_eq!(
+        key("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+            ^ key("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"),
+        white_pawn(Square::e2) ^ white_pawn(Square::e4) ^ randoms[TURN_OFFSET]
+    );
+
+    // 1.e4 d5 2.e5 f5: the pawn on e5 can capture on f6, and so the file is hashed
+    assert_eq!(
+        key("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+            ^ key("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3"),
+        randoms[EN_PASSANT_OFFSET + BoardFile::f_.ix()]
+    );
+
+    assert_eq!(
+        key("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+            ^ key("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w Qkq - 0 1"),
+        randoms[CASTLE_OFFSET]
+    );
+}
+
+#[test]
+fn book_key_test() {
+    use crate::{
+        bitboard::{board::FullerBitBoard, hash::FullZobristTables},
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    let key = |fen: &str| {
+        book_key(
+            &FenBoard::parser()
+                .parse(fen)
+                .into_result()
+                .unwrap()
+                .as_bitboard::<FullerBitBoard, FullZobristTables>(),
+        )
+    };
+
+    // The move counters are not part of the key, but the active player is
+    assert_eq!(
+        key("8/8/8/4k3/8/8/4K3/8 w - - 0 1"),
+        key("8/8/8/4k3/8/8/4K3/8 w - - 10 30")
+    );
+    assert_ne!(
+        key("8/8/8/4k3/8/8/4K3/8 w - - 0 1"),
+        key("8/8/8/4k3/8/8/4K3/8 b - - 0 1")
+    );
+}
+
+/// Length in bytes of a Polyglot book entry.
+pub const POLYGLOT_ENTRY_LEN: usize = 16;
+
+/// An entry of a Polyglot opening book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolyglotEntry {
+    pub key: u64,
+    /// The book move. Castling is written as the king capturing its own rook,
+    /// as in the book, e.g. `e1h1` for white castling kingside.
+    pub mv: CoordNotation,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+impl PolyglotEntry {
+    /// Decode a single big-endian 16 byte book entry.
+    pub fn decode(bytes: &[u8; POLYGLOT_ENTRY_LEN]) -> Self {
+        let key = u64::from_be_bytes(bytes[0..8].try_into().unwrap());
+        let mv = u16::from_be_bytes([bytes[8], bytes[9]]);
+        let weight = u16::from_be_bytes([bytes[10], bytes[11]]);
+        let learn = u32::from_be_bytes(bytes[12..16].try_into().unwrap());
+
+        // Bits 0-5 are the destination, 6-11 the origin, both laid out as our squares,
+        // and 12-14 the promotion, 0 for none and 1-4 for knight through queen
+        let to = Square::from_u8(mv as u8);
+        let from = Square::from_u8((mv >> 6) as u8);
+        let prom = match (mv >> 12) & 0x7 {
+            1 => Some(PawnPromotion::KNIGHT),
+            2 => Some(PawnPromotion::BISHOP),
+            3 => Some(PawnPromotion::ROOK),
+            4 => Some(PawnPromotion::QUEEN),
+            _ => None,
+        };
+
+        Self {
+            key,
+            mv: CoordNotation::new(from, to, prom),
+            weight,
+            learn,
+        }
+    }
+}
+
+/// Find all the entries of a Polyglot opening book with the given key.
+///
+/// The book is the raw contents of a `.bin` file, which is sorted by key.
+/// Any trailing bytes not making up a whole entry are ignored.
+pub fn probe_book(book: &[u8], key: u64) -> Vec<PolyglotEntry> {
+    let entries = book.as_chunks::<POLYGLOT_ENTRY_LEN>().0;
+    let key_of =
+        |entry: &[u8; POLYGLOT_ENTRY_LEN]| u64::from_be_bytes(entry[0..8].try_into().unwrap());
+
+    let start = entries.partition_point(|entry| key_of(entry) < key);
+
+    entries[start..]
+        .iter()
+        .take_while(|entry| key_of(entry) == key)
+        .map(PolyglotEntry::decode)
+        .collect()
+}
+
+#[test]
+fn probe_book_test() {
+    fn entry(key: u64, mv: u16, weight: u16) -> Vec<u8> {
+        let mut res = key.to_be_bytes().to_vec();
+        res.extend(mv.to_be_bytes());
+        res.extend(weight.to_be_bytes());
+        res.extend(0u32.to_be_bytes());
+        res
+    }
+
+    let sq = |sq: Square| sq as u16;
+
+    let book = [
+        entry(1, sq(Square::d2) << 6 | sq(Square::d4), 10),
+        entry(7, sq(Square::e2) << 6 | sq(Square::e4), 20),
+        entry(7, sq(Square::g1) << 6 | sq(Square::f3), 5),
+        entry(7, 4 << 12 | sq(Square::a7) << 6 | sq(Square::a8), 1),
+        entry(9, sq(Square::e1) << 6 | sq(Square::h1), 3),
+    ]
+    .concat();
+
+    let entries = probe_book(&book, 7);
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].mv.to_string(), "e2e4");
+    assert_eq!(entries[0].weight, 20);
+    assert_eq!(entries[1].mv.to_string(), "g1f3");
+    assert_eq!(entries[2].mv.to_string(), "a7a8q");
+    assert_eq!(entries[2].weight, 1);
+
+    assert_eq!(probe_book(&book, 9)[0].mv.to_string(), "e1h1");
+    assert!(probe_book(&book, 8).is_empty());
+    assert_eq!(probe_book(&book[..40], 7).len(), 1);
+}
@@ -0,0 +1,297 @@
+//! Standard algebraic notation of legal moves.
+//!
+//! Writing a move in SAN requires knowledge of the position: which
+//! other pieces could reach the same square, and whether the move
+//! gives check or mate.
+
+use std::fmt::Display;
+
+use crate::{
+    bitboard::{
+        attacking::king_in_check,
+        board::BitBoard,
+        hash::{NoHashes, ZobristTables},
+        movegen::{BlessingStrategy, enumerate, enumerate_piece},
+        moving::{clone_make_legal_move, make_legal_move},
+        status::{GameStatus, game_status},
+        vision::Panopticon,
+    },
+    model::*,
+    notation::{InCheck, StdAlgCastling, StdAlgNotation, StdAlgOfficer, StdAlgPawn, fen::FenBoard},
+};
+
+/// Write a legal move in standard algebraic notation.
+///
+/// Officer moves are disambiguated by file if that suffices,
+/// otherwise by rank, and otherwise by both, per FIDE appendix C.10.
+pub fn san<BB, X, L>(board: &BB, mv: LegalMove) -> StdAlgNotation
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    let player = board.ply().0;
+    let after = clone_make_legal_move::<BB, NoHashes>(board, mv);
+    let in_check = if !king_in_check::<BB, X>(&after, player.opp()) {
+        None
+    } else if game_status::<BB, X, L>(&after) == GameStatus::Checkmate {
+        Some(InCheck::Mate)
+    } else {
+        Some(InCheck::Check)
+    };
+
+    let LegalMove(mv) = mv;
+
+    if let Some(dir) = CastlingDirection::from_special(mv.special) {
+        return match dir {
+            CastlingDirection::EAST => StdAlgCastling::OOO(in_check),
+            CastlingDirection::WEST => StdAlgCastling::OO(in_check),
+        }
+        .into();
+    }
+
+    let Some(officer) = ChessOfficer::from_piece(mv.ech) else {
+        return StdAlgPawn::new(
+            mv.capture.map(|_| mv.from.coords().0),
+            mv.to,
+            PawnPromotion::from_special(mv.special),
+            in_check,
+        )
+        .into();
+    };
+
+    let (file, rank) = mv.from.coords();
+    let mut rivals = vec![];
+    let mut moves = vec![];
+    enumerate::<BB, X, L>(board, &mut moves);
+    for LegalMove(other) in moves {
+        if other.ech == mv.ech && other.to == mv.to && other.from != mv.from {
+            rivals.push(other.from.coords());
+        }
+    }
+
+    let (from_file, from_rank) = if rivals.is_empty() {
+        (None, None)
+    } else if rivals.iter().all(|&(f, _)| f != file) {
+        (Some(file), None)
+    } else if rivals.iter().all(|&(_, r)| r != rank) {
+        (None, Some(rank))
+    } else {
+        (Some(file), Some(rank))
+    };
+
+    StdAlgOfficer::new(
+        officer,
+        from_file,
+        from_rank,
+        mv.capture.is_some(),
+        mv.to,
+        in_check,
+    )
+    .into()
+}
+
+/// Write castling in the given direction in standard algebraic notation,
+/// with the check or mate suffix it earns.
+///
+/// Returns `None` if castling that way is not legal.
+pub fn castling_san<BB, X, L>(board: &BB, dir: CastlingDirection) -> Option<StdAlgCastling>
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    let mut moves = vec![];
+    enumerate_piece::<BB, X, L>(board, ChessPiece::KING, &mut moves);
+
+    let mv = moves
+        .into_iter()
+        .find(|mv| CastlingDirection::from_special(mv.0.special) == Some(dir))?;
+
+    match san::<BB, X, L>(board, mv) {
+        StdAlgNotation::Castling(castling) => Some(castling),
+        _ => unreachable!(),
+    }
+}
+
+/// Find the legal move written by a string of SAN.
+///
+/// Check and annotation suffixes are ignored.
+pub fn resolve_san<BB, X, L>(board: &BB, text: &str) -> Option<LegalMove>
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    let strip = |s: &str| s.trim_end_matches(['+', '#', '!', '?']).to_string();
+    let text = strip(text);
+
+    let mut moves = vec![];
+    enumerate::<BB, X, L>(board, &mut moves);
+    moves
+        .into_iter()
+        .find(|&mv| strip(&san::<BB, X, L>(board, mv).to_string()) == text)
+}
+
+/// A move in a sequence of SAN which could not be played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanError {
+    /// Index of the move in the sequence.
+    pub ply: usize,
+    pub san: String,
+    /// The position in which the move is not legal.
+    pub fen: String,
+}
+
+impl Display for SanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is not a legal move in {}", self.san, self.fen)
+    }
+}
+
+/// Play a sequence of moves in SAN from the starting board, returning the final board.
+pub fn play_sans<BB, X, L, ZT>(start: BB, sans: &[&str]) -> Result<BB, SanError>
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    ZT: ZobristTables,
+{
+    let mut board = start;
+
+    for (ply, text) in sans.iter().enumerate() {
+        let Some(mv) = resolve_san::<BB, X, L>(&board, text) else {
+            return Err(SanError {
+                ply,
+                san: text.to_string(),
+                fen: FenBoard::from_bitboard(&board).to_string(),
+            });
+        };
+        make_legal_move::<BB, ZT>(&mut board, mv);
+    }
+
+    Ok(board)
+}
+
+#[test]
+fn play_sans_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy,
+        board::{ChessBoard, FullerBitBoard},
+        hash::FullZobristTables,
+        movegen::LegalBlessing,
+        vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+    type ZT = FullZobristTables;
+
+    let play = |sans: &[&str]| {
+        play_sans::<_, MostlyBits, Legal, ZT>(FullerBitBoard::startpos::<ZT>(), sans)
+    };
+
+    let italian = play(&["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5"]).unwrap();
+    assert_eq!(
+        FenBoard::from_bitboard(&italian).to_string(),
+        "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4"
+    );
+
+    let err = play(&["e4", "e5", "Ke3"]).unwrap_err();
+    assert_eq!(err.ply, 2);
+    assert_eq!(err.san, "Ke3");
+}
+
+#[test]
+fn san_test() {
+    use chumsky::Parser;
+
+    use crate::{
+        bitboard::{
+            attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+            movegen::LegalBlessing, vision::MostlyBits,
+        },
+        notation::{Parsable, fen::FenBoard},
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let sans = |fen: &str| {
+        let board = FenBoard::parser()
+            .parse(fen)
+            .into_result()
+            .unwrap()
+            .as_bitboard::<FullerBitBoard, FullZobristTables>();
+        let mut moves = vec![];
+        enumerate::<FullerBitBoard, MostlyBits, Legal>(&board, &mut moves);
+        moves
+            .into_iter()
+            .map(|mv| san::<FullerBitBoard, MostlyBits, Legal>(&board, mv).to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let moves = sans("4k3/8/8/R7/8/8/4K3/R6R w - - 0 1");
+    for mv in ["Rad1", "Rhd1", "R1a3", "R5a3", "Ra8+", "Rh8+", "Re5+"] {
+        assert!(moves.contains(&mv.to_string()), "{mv} not in {moves:?}");
+    }
+
+    let moves = sans("r3k2r/8/1P6/8/8/8/8/4K3 b kq - 0 1");
+    for mv in ["O-O", "O-O-O", "Rd8", "Ra2"] {
+        assert!(moves.contains(&mv.to_string()), "{mv} not in {moves:?}");
+    }
+
+    let moves = sans("3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1");
+    for mv in ["exd8=Q+", "exd8=R+", "exd8=B", "e8=Q+", "e8=N"] {
+        assert!(moves.contains(&mv.to_string()), "{mv} not in {moves:?}");
+    }
+
+    let board = FenBoard::parser()
+        .parse("3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1")
+        .into_result()
+        .unwrap()
+        .as_bitboard::<FullerBitBoard, FullZobristTables>();
+    let resolve = |text| resolve_san::<_, MostlyBits, Legal>(&board, text).unwrap().0;
+    assert!(resolve("exd8=Q+").is_promotion_capture());
+    assert!(!resolve("e8=Q+").is_promotion_capture());
+    assert!(!resolve("Kf2").is_promotion_capture());
+
+    let moves = sans("3r3k/4P1pp/8/8/8/8/8/4K3 w - - 0 1");
+    for mv in ["exd8=Q#", "exd8=R#", "e8=Q+", "e8=N"] {
+        assert!(moves.contains(&mv.to_string()), "{mv} not in {moves:?}");
+    }
+
+    let moves = sans("r3k2r/1P6/8/8/8/8/8/4K3 w - - 0 1");
+    for mv in ["bxa8=Q+", "b8=N", "Kd2"] {
+        assert!(moves.contains(&mv.to_string()), "{mv} not in {moves:?}");
+    }
+}
+
+#[test]
+fn castling_san_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+        movegen::LegalBlessing, setup::parse_position, vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let castling = |fen, dir| {
+        let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+        castling_san::<_, MostlyBits, Legal>(&board, dir).map(|c| c.to_string())
+    };
+
+    use CastlingDirection::*;
+    assert_eq!(
+        castling("5k2/8/8/8/8/8/8/4K2R w K - 0 1", WEST).as_deref(),
+        Some("O-O+")
+    );
+    assert_eq!(
+        castling("4rkr1/4p1p1/8/8/8/8/8/4K2R w K - 0 1", WEST).as_deref(),
+        Some("O-O#")
+    );
+    assert_eq!(
+        castling("r3k3/8/8/8/8/8/8/5K2 b q - 0 1", EAST).as_deref(),
+        Some("O-O-O")
+    );
+    assert_eq!(castling("5k2/8/8/8/8/8/8/4K2R w K - 0 1", EAST), None);
+    assert_eq!(castling("5k2/8/8/8/8/8/8/4K2R w - - 0 1", WEST), None);
+}
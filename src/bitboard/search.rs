@@ -0,0 +1,284 @@
+//! Game tree search.
+
+use std::cmp::Reverse;
+
+use crate::bitboard::{
+    board::BitBoard,
+    eval::MATERIAL,
+    hash::ZobristTables,
+    movegen::{BlessingStrategy, enumerate},
+    moving::{make_legal_move, unmake_legal_move},
+    status::{GameStatus, game_status},
+    vision::Panopticon,
+};
+
+use crate::model::*;
+
+/// Find a move which forces checkmate within `n` moves of the active player.
+///
+/// This is an alpha-beta search with a null window, in which the only scores
+/// are checkmate and not checkmate, so every line is cut off as soon as the
+/// defender is found to have a single escape.
+pub fn mate_in<BB, X, L, ZT>(board: &BB, n: usize) -> Option<LegalMove>
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    ZT: ZobristTables,
+{
+    let mut board = board.clone();
+    attack::<BB, X, L, ZT>(&mut board, n)
+}
+
+/// A move of the attacker after which the defender is mated within `n` moves.
+fn attack<BB, X, L, ZT>(board: &mut BB, n: usize) -> Option<LegalMove>
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    ZT: ZobristTables,
+{
+    if n == 0 {
+        return None;
+    }
+
+    let mut moves = vec![];
+    enumerate::<BB, X, L>(board, &mut moves);
+
+    moves.into_iter().find(|&mv| {
+        let trans = make_legal_move::<BB, ZT>(board, mv);
+        let mated = defend::<BB, X, L, ZT>(board, n - 1);
+        unmake_legal_move::<BB, ZT>(board, mv, trans);
+        mated
+    })
+}
+
+/// Whether the defender is mated within `n` further moves of the attacker, whatever they play.
+fn defend<BB, X, L, ZT>(board: &mut BB, n: usize) -> bool
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    ZT: ZobristTables,
+{
+    match game_status::<BB, X, L>(board) {
+        GameStatus::Checkmate => return true,
+        GameStatus::Stalemate => return false,
+        _ if n == 0 => return false,
+        _ => {}
+    }
+
+    let mut moves = vec![];
+    enumerate::<BB, X, L>(board, &mut moves);
+
+    moves.into_iter().all(|mv| {
+        let trans = make_legal_move::<BB, ZT>(board, mv);
+        let mated = attack::<BB, X, L, ZT>(board, n).is_some();
+        unmake_legal_move::<BB, ZT>(board, mv, trans);
+        mated
+    })
+}
+
+/// The score of delivering checkmate right away; mates further away
+/// score one less for every ply until the mate.
+pub const MATE: i32 = 1_000_000;
+
+/// Iterative deepening alpha-beta negamax search to `max_depth` plies.
+///
+/// The `eval` function scores quiet leaves from the point of view of the active player,
+/// while checkmate, stalemate, and the 50-move rule are scored by [`game_status`]
+/// regardless. Moves are searched in MVV-LVA order, with the best move of the
+/// previous iteration first.
+///
+/// Returns the best move, if there are any legal moves, and its score.
+pub fn search<BB, X, L, ZT>(
+    board: &BB,
+    max_depth: usize,
+    eval: impl Fn(&BB) -> i32,
+) -> (Option<LegalMove>, i32)
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    ZT: ZobristTables,
+{
+    let mut board = board.clone();
+    let mut moves = vec![];
+    enumerate::<BB, X, L>(&board, &mut moves);
+    order_moves(&mut moves);
+
+    let mut res = (
+        None,
+        negamax::<BB, X, L, ZT>(&mut board, 0, 0, -MATE, MATE, &eval),
+    );
+
+    for depth in 1..=max_depth {
+        if moves.is_empty() {
+            break;
+        }
+
+        let mut alpha = -MATE;
+        let mut best = moves[0];
+        for &mv in &moves {
+            let trans = make_legal_move::<BB, ZT>(&mut board, mv);
+            let score = -negamax::<BB, X, L, ZT>(&mut board, depth - 1, 1, -MATE, -alpha, &eval);
+            unmake_legal_move::<BB, ZT>(&mut board, mv, trans);
+
+            if score > alpha {
+                alpha = score;
+                best = mv;
+            }
+        }
+
+        res = (Some(best), alpha);
+        let ix = moves.iter().position(|&mv| mv == best).unwrap();
+        moves[..=ix].rotate_right(1);
+
+        if alpha.abs() > MATE - 1000 {
+            break;
+        }
+    }
+
+    res
+}
+
+/// The score of the position for the active player, searched `depth` plies deep,
+/// `height` plies below the root.
+fn negamax<BB, X, L, ZT>(
+    board: &mut BB,
+    depth: usize,
+    height: i32,
+    mut alpha: i32,
+    beta: i32,
+    eval: &impl Fn(&BB) -> i32,
+) -> i32
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    ZT: ZobristTables,
+{
+    match game_status::<BB, X, L>(board) {
+        GameStatus::Checkmate => return -MATE + height,
+        GameStatus::Stalemate | GameStatus::FiftyMoveRule => return 0,
+        GameStatus::Ongoing if depth == 0 => return eval(board),
+        GameStatus::Ongoing => {}
+    }
+
+    let mut moves = vec![];
+    enumerate::<BB, X, L>(board, &mut moves);
+    order_moves(&mut moves);
+
+    for mv in moves {
+        let trans = make_legal_move::<BB, ZT>(board, mv);
+        let score = -negamax::<BB, X, L, ZT>(board, depth - 1, height + 1, -beta, -alpha, eval);
+        unmake_legal_move::<BB, ZT>(board, mv, trans);
+
+        if score >= beta {
+            return beta;
+        }
+        alpha = alpha.max(score);
+    }
+
+    alpha
+}
+
+/// Sort captures first, most valuable victim first and then least valuable attacker first.
+fn order_moves(moves: &mut [LegalMove]) {
+    moves.sort_by_key(|mv| {
+        let victim =
+            mv.0.capture
+                .map_or(0, |c| MATERIAL[ChessPiece::from(c).ix()]);
+        (
+            Reverse(victim),
+            mv.0.capture.is_some().then(|| MATERIAL[mv.0.ech.ix()]),
+        )
+    });
+}
+
+#[test]
+fn mate_in_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+        movegen::LegalBlessing, moving::clone_make_legal_move, san::san, setup::parse_position,
+        vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+    type ZT = FullZobristTables;
+
+    let mate_in = mate_in::<FullerBitBoard, MostlyBits, Legal, ZT>;
+
+    let (board, _, _) =
+        parse_position::<FullerBitBoard, ZT>("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+    let mv = mate_in(&board, 1).unwrap();
+    assert_eq!(san::<_, MostlyBits, Legal>(&board, mv).to_string(), "Ra8#");
+
+    let (board, _, _) =
+        parse_position::<FullerBitBoard, ZT>("7k/8/5K2/8/8/8/8/R7 w - - 0 1").unwrap();
+    assert_eq!(mate_in(&board, 1), None);
+
+    let mv = mate_in(&board, 2).unwrap();
+    let after = clone_make_legal_move::<_, ZT>(&board, mv);
+    let mut replies = vec![];
+    enumerate::<_, MostlyBits, Legal>(&after, &mut replies);
+    assert!(!replies.is_empty());
+    for reply in replies {
+        let after = clone_make_legal_move::<_, ZT>(&after, reply);
+        assert!(mate_in(&after, 1).is_some());
+    }
+}
+
+#[test]
+fn search_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy,
+        board::{FullerBitBoard, MetaBoard},
+        hash::FullZobristTables,
+        movegen::LegalBlessing,
+        san::san,
+        setup::parse_position,
+        vision::MostlyBits,
+    };
+    use strum::VariantArray;
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+    type ZT = FullZobristTables;
+
+    let search = search::<FullerBitBoard, MostlyBits, Legal, ZT>;
+
+    let (board, _, _) =
+        parse_position::<FullerBitBoard, ZT>("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+    for eval in [|_: &FullerBitBoard| 0, |_: &FullerBitBoard| -5000] {
+        let (mv, score) = search(&board, 3, eval);
+        assert_eq!(
+            san::<_, MostlyBits, Legal>(&board, mv.unwrap()).to_string(),
+            "Ra8#"
+        );
+        assert!(score > MATE - 1000);
+    }
+
+    let (board, _, _) =
+        parse_position::<FullerBitBoard, ZT>("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(search(&board, 3, |_| 1), (None, 0));
+
+    let (board, _, _) =
+        parse_position::<FullerBitBoard, ZT>("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1").unwrap();
+    let material = |b: &FullerBitBoard| {
+        let player = b.ply().0;
+        ChessPiece::VARIANTS[..5]
+            .iter()
+            .map(|&ech| {
+                MATERIAL[ech.ix()]
+                    * (b.men(player, ech).count_ones() as i32
+                        - b.men(player.opp(), ech).count_ones() as i32)
+            })
+            .sum::<i32>()
+    };
+    let (mv, score) = search(&board, 2, material);
+    assert_eq!(
+        san::<_, MostlyBits, Legal>(&board, mv.unwrap()).to_string(),
+        "Rxd5"
+    );
+    assert_eq!(score, MATERIAL[ChessPiece::ROOK.ix()]);
+}
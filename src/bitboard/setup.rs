@@ -14,8 +14,11 @@
 use strum::VariantArray;
 
 use crate::{
-    bitboard::{board::BitBoard, utils::SliceExtensions, vision::SimplePanopticon},
+    bitboard::{
+        board::BitBoard, hash::ZobristTables, utils::SliceExtensions, vision::SimplePanopticon,
+    },
     biterate,
+    notation::fen::{ColorCase, FenBoard, FenError, parse_fen, write_board_field},
 };
 
 use crate::model::*;
@@ -63,3 +66,279 @@ impl DataBoard<Option<ChessMan>> {
         bb
     }
 }
+
+impl FenBoard {
+    /// Describe a bitboard position in FEN.
+    pub fn from_bitboard<BB: BitBoard>(bb: &BB) -> Self {
+        let (to_move, turn) = bb.ply();
+        let trans = bb.trans();
+
+        let mut castling_rights = vec![];
+        for dir in [CastlingDirection::WEST, CastlingDirection::EAST] {
+            if trans.rights[ChessColor::WHITE.ix()][dir.ix()] {
+                castling_rights.push(ColorCase::White(dir));
+            }
+        }
+        for dir in [CastlingDirection::WEST, CastlingDirection::EAST] {
+            if trans.rights[ChessColor::BLACK.ix()][dir.ix()] {
+                castling_rights.push(ColorCase::Black(dir));
+            }
+        }
+
+        Self {
+            board: DataBoard::from_bitboard(bb),
+            to_move,
+            castling_rights,
+            en_passant: trans.en_passant.map(|ep| ep.target_square()),
+            halfmove_clock: trans.halfmove_clock,
+            turn,
+        }
+    }
+
+    /// Set up a bitboard from a parsed FEN position, including
+    /// the transients, the active player, and the Zobrist hash.
+    pub fn as_bitboard<BB: BitBoard, ZT: ZobristTables>(&self) -> BB {
+        let mut bb = self.board.as_bitboard::<BB>();
+
+        let en_passant = self.en_passant.map(|square| EnPassant {
+            square,
+            capture: match self.to_move {
+                ChessColor::WHITE => Square::from_u8(square as u8 - 8),
+                ChessColor::BLACK => Square::from_u8(square as u8 + 8),
+            },
+        });
+
+        bb.set_transients(Transients {
+            en_passant,
+            halfmove_clock: self.halfmove_clock,
+            rights: [[false; 2]; 2],
+        });
+
+        loop {
+            let (player, turn) = bb.ply();
+            if (turn, player) >= (self.turn.max(1), self.to_move) {
+                break;
+            }
+            bb.next_ply();
+        }
+
+        bb.hash(bb.curr_hash() ^ bb.rehash::<ZT>());
+
+        apply_castling_rights::<BB, ZT>(&mut bb, &self.castling_rights);
+
+        bb
+    }
+}
+
+/// Parse a FEN string straight into a board, along with the active player and turn number.
+pub fn parse_position<BB: BitBoard, ZT: ZobristTables>(
+    fen: &str,
+) -> Result<(BB, ChessColor, u16), FenError> {
+    let board = parse_fen(fen)?.as_bitboard::<BB, ZT>();
+    let (player, turn) = board.ply();
+
+    Ok((board, player, turn))
+}
+
+/// The piece placement field of the FEN of a position, without the other fields.
+pub fn board_field<BB: BitBoard>(board: &BB) -> String {
+    let mut res = String::new();
+    write_board_field(&mut res, &DataBoard::from_bitboard(board)).unwrap();
+    res
+}
+
+/// Grant the given castling rights on a board, updating the Zobrist hash.
+///
+/// Rights already held are kept, and rights not mentioned are not revoked.
+pub fn apply_castling_rights<BB: BitBoard, ZT: ZobristTables>(
+    board: &mut BB,
+    rights: &[ColorCase<CastlingDirection>],
+) {
+    let zobristhashes = ZT::static_table();
+    let mut new_rights = board.trans().rights;
+
+    board.hash(zobristhashes.hash_rights(new_rights));
+
+    for case in rights.clones() {
+        match case {
+            ColorCase::White(dir) => new_rights[ChessColor::WHITE.ix()][dir.ix()] = true,
+            ColorCase::Black(dir) => new_rights[ChessColor::BLACK.ix()][dir.ix()] = true,
+        }
+    }
+
+    board.set_castling_rights(new_rights);
+    board.hash(zobristhashes.hash_rights(new_rights));
+}
+
+/// Infer the castling rights of a position lacking them, granting a right
+/// only when both the king and the rook of that side are on their starting squares.
+pub fn infer_castling_rights<BB: BitBoard>(board: &BB, rules: &CastlingRules) -> [[bool; 2]; 2] {
+    let mut res = [[false; 2]; 2];
+
+    for color in [ChessColor::WHITE, ChessColor::BLACK] {
+        let king = board.men(color, ChessPiece::KING) & 1 << rules.king_start[color.ix()] as u8;
+        if king == 0 {
+            continue;
+        }
+
+        for dir in [CastlingDirection::EAST, CastlingDirection::WEST] {
+            let rook = 1 << rules.rook_start[color.ix()][dir.ix()] as u8;
+            res[color.ix()][dir.ix()] = board.men(color, ChessPiece::ROOK) & rook != 0;
+        }
+    }
+
+    res
+}
+
+#[test]
+fn fen_round_trip_test() {
+    use crate::{
+        bitboard::{board::FullerBitBoard, hash::FullZobristTables},
+        notation::Parsable,
+    };
+    use chumsky::Parser;
+
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 12 40",
+    ];
+
+    for fen in fens {
+        let board = FenBoard::parser()
+            .parse(fen)
+            .into_result()
+            .unwrap()
+            .as_bitboard::<FullerBitBoard, FullZobristTables>();
+
+        assert_eq!(FenBoard::from_bitboard(&board).to_string(), fen);
+    }
+}
+
+#[test]
+fn board_field_test() {
+    use crate::bitboard::{
+        board::{ChessBoard, FullerBitBoard},
+        hash::FullZobristTables,
+    };
+
+    let board = FullerBitBoard::startpos::<FullZobristTables>();
+    assert_eq!(
+        board_field(&board),
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR"
+    );
+
+    let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 12 40",
+    )
+    .unwrap();
+    assert_eq!(board_field(&board), "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8");
+}
+
+#[test]
+fn apply_castling_rights_test() {
+    use crate::bitboard::{
+        board::{ChessBoard, CompactBitBoard, MetaBoard},
+        hash::FullZobristTables,
+    };
+
+    let mut board = CompactBitBoard::startpos::<FullZobristTables>();
+    board.set_castling_rights([[false; 2]; 2]);
+    board.hash(board.curr_hash() ^ board.rehash::<FullZobristTables>());
+    assert_ne!(
+        board.curr_hash(),
+        CompactBitBoard::startpos::<FullZobristTables>().curr_hash()
+    );
+
+    use CastlingDirection::*;
+    apply_castling_rights::<_, FullZobristTables>(
+        &mut board,
+        &[
+            ColorCase::White(WEST),
+            ColorCase::White(EAST),
+            ColorCase::Black(WEST),
+            ColorCase::Black(EAST),
+        ],
+    );
+
+    assert_eq!(board.trans().rights, [[true; 2]; 2]);
+    assert_eq!(board.curr_hash(), board.rehash::<FullZobristTables>());
+    assert_eq!(
+        board.curr_hash(),
+        CompactBitBoard::startpos::<FullZobristTables>().curr_hash()
+    );
+}
+
+#[test]
+fn fen_setup_test() {
+    use crate::{
+        bitboard::{
+            board::{ChessBoard, CompactBitBoard, MetaBoard},
+            hash::FullZobristTables,
+        },
+        notation::Parsable,
+    };
+    use chumsky::Parser;
+
+    let board = FenBoard::parser()
+        .parse("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+        .into_result()
+        .unwrap()
+        .as_bitboard::<CompactBitBoard, FullZobristTables>();
+
+    let startpos = CompactBitBoard::startpos::<FullZobristTables>();
+
+    assert_eq!(board, startpos);
+    assert_eq!(board.curr_hash(), startpos.curr_hash());
+}
+
+#[test]
+fn parse_position_test() {
+    use crate::bitboard::{board::FullerBitBoard, hash::FullZobristTables};
+
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 17";
+    let (board, player, turn) =
+        parse_position::<FullerBitBoard, FullZobristTables>(kiwipete).unwrap();
+
+    assert_eq!(player, ChessColor::BLACK);
+    assert_eq!(turn, 17);
+    assert_eq!(
+        board.men(ChessColor::WHITE, ChessPiece::QUEEN),
+        1 << Square::f3.ix()
+    );
+    assert_eq!(FenBoard::from_bitboard(&board).to_string(), kiwipete);
+
+    assert!(matches!(
+        parse_position::<FullerBitBoard, FullZobristTables>("8/8/8 w - - 0 1"),
+        Err(FenError::Syntax(_))
+    ));
+    assert!(matches!(
+        parse_position::<FullerBitBoard, FullZobristTables>("4k3/8/8/8/8/8/8/4K3 w K - 0 1"),
+        Err(FenError::Position(_))
+    ));
+}
+
+#[test]
+fn infer_castling_rights_test() {
+    use crate::bitboard::{board::FullerBitBoard, hash::FullZobristTables};
+
+    let infer = |fen| {
+        let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+        infer_castling_rights(&board, &CastlingRules::STANDARD)
+    };
+
+    assert_eq!(
+        infer("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1"),
+        [[true; 2]; 2]
+    );
+    assert_eq!(
+        infer("rnbq1bnr/ppppkppp/8/8/8/8/PPPPKPPP/RNBQ1BNR w - - 0 1"),
+        [[false; 2]; 2]
+    );
+    assert_eq!(
+        infer("1nbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w - - 0 1"),
+        [[true, false], [false, true]]
+    );
+}
@@ -0,0 +1,285 @@
+//! Game termination.
+//!
+//! Detection of the positions in which the game is over
+//! by the rules of chess, rather than by agreement or resignation.
+
+use rand::{Rng, RngCore};
+
+use crate::bitboard::{
+    attacking::{checkers, en_passant_is_capturable, king_in_check},
+    board::BitBoard,
+    hash::ZobristTables,
+    movegen::{BlessingStrategy, enumerate, enumerate_king, legal_en_passant},
+    vision::Panopticon,
+};
+
+use crate::model::*;
+
+/// Whether, and how, a game has ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameStatus {
+    /// The active player has legal moves and no draw can be claimed.
+    Ongoing,
+    /// The active player is in check and has no legal moves.
+    Checkmate,
+    /// The active player is not in check and has no legal moves.
+    Stalemate,
+    /// A hundred plies have passed without a capture or pawn move.
+    FiftyMoveRule,
+}
+
+/// Determine the status of the game for the active player.
+///
+/// In double check only the king can move, so only king moves are generated.
+/// Checkmate takes precedence over the 50-move rule, per FIDE article 9.3.
+pub fn game_status<BB, X, L>(board: &BB) -> GameStatus
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    let player = board.ply().0;
+    let check = checkers::<BB, X>(board, player);
+
+    let mut moves = vec![];
+    if check.count_ones() >= 2 {
+        enumerate_king::<BB, X, L>(board, &mut moves);
+    } else {
+        enumerate::<BB, X, L>(board, &mut moves);
+    }
+
+    if moves.is_empty() {
+        if check != 0 {
+            GameStatus::Checkmate
+        } else {
+            GameStatus::Stalemate
+        }
+    } else if board.trans().halfmove_clock >= 100 {
+        GameStatus::FiftyMoveRule
+    } else {
+        GameStatus::Ongoing
+    }
+}
+
+/// Whether the position could have arisen by legal play, as far as checks go:
+/// the player who just moved cannot have left their king in check.
+pub fn is_position_legal<BB: BitBoard, X: Panopticon>(board: &BB) -> bool {
+    !king_in_check::<BB, X>(board, board.ply().0.opp())
+}
+
+/// The Zobrist hash of the board, as far as repetition of position goes.
+///
+/// Per FIDE article 9.2.2 positions are the same only if the same moves
+/// are possible, so the en-passant square only counts while an
+/// en-passant capture is actually legal; a pawn that could geometrically
+/// capture but is pinned does not make the position different.
+pub fn repetition_key<BB, X, L, ZT>(board: &BB) -> u64
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    ZT: ZobristTables,
+{
+    let ep = board.trans().en_passant;
+    if en_passant_is_capturable::<BB, X>(board) && !legal_en_passant::<BB, X, L>(board).is_empty() {
+        board.curr_hash()
+    } else {
+        board.curr_hash() ^ ZT::static_table().hash_en_passant(ep)
+    }
+}
+
+/// Whether the last of a sequence of [`repetition_key`]s has
+/// occurred at least three times, so that a draw can be claimed.
+pub fn is_threefold_repetition(keys: &[u64]) -> bool {
+    let Some(last) = keys.last() else {
+        return false;
+    };
+
+    keys.iter().filter(|&k| k == last).count() >= 3
+}
+
+/// Pick one of the legal moves of the active player uniformly at random,
+/// for instance to play out random games.
+///
+/// Returns `None` if there are no legal moves.
+pub fn random_legal_move<BB, X, L>(board: &BB, rng: &mut impl RngCore) -> Option<LegalMove>
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+{
+    let mut moves = vec![];
+    enumerate::<BB, X, L>(board, &mut moves);
+
+    if moves.is_empty() {
+        None
+    } else {
+        Some(moves[rng.random_range(0..moves.len())])
+    }
+}
+
+#[test]
+fn random_games_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy,
+        board::{ChessBoard, FullerBitBoard, MetaBoard},
+        hash::FullZobristTables,
+        movegen::LegalBlessing,
+        moving::clone_make_legal_move,
+        vision::MostlyBits,
+    };
+    use rand::{SeedableRng, rngs::SmallRng};
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    for seed in 0..40 {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut board = FullerBitBoard::startpos::<FullZobristTables>();
+
+        let status = loop {
+            let status = game_status::<_, MostlyBits, Legal>(&board);
+            if status != GameStatus::Ongoing {
+                break status;
+            }
+
+            let mv = random_legal_move::<_, MostlyBits, Legal>(&board, &mut rng)
+                .expect("ongoing game has legal moves");
+            board = clone_make_legal_move::<_, FullZobristTables>(&board, mv);
+        };
+
+        let mut moves = vec![];
+        enumerate::<_, MostlyBits, Legal>(&board, &mut moves);
+        match status {
+            GameStatus::Checkmate | GameStatus::Stalemate => assert!(moves.is_empty()),
+            GameStatus::FiftyMoveRule => assert!(board.trans().halfmove_clock >= 100),
+            GameStatus::Ongoing => unreachable!(),
+        }
+    }
+}
+
+#[test]
+fn game_status_test() {
+    use crate::{
+        bitboard::{
+            attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+            movegen::LegalBlessing, vision::MostlyBits,
+        },
+        notation::{Parsable, fen::FenBoard},
+    };
+    use chumsky::Parser;
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    fn board(fen: &str) -> FullerBitBoard {
+        FenBoard::parser()
+            .parse(fen)
+            .into_result()
+            .unwrap()
+            .as_bitboard::<FullerBitBoard, FullZobristTables>()
+    }
+
+    fn status(fen: &str) -> GameStatus {
+        game_status::<_, MostlyBits, Legal>(&board(fen))
+    }
+
+    // Double check by the knight and the rook, which the fast path must
+    // agree with the full generator is mate
+    let mate = "3rkb2/3p1p2/3N4/8/8/8/8/4R1K1 b - - 0 1";
+    let mut moves = vec![];
+    enumerate::<_, MostlyBits, Legal>(&board(mate), &mut moves);
+    assert!(moves.is_empty());
+    assert_eq!(
+        checkers::<_, MostlyBits>(&board(mate), ChessColor::BLACK).count_ones(),
+        2
+    );
+    assert_eq!(status(mate), GameStatus::Checkmate);
+
+    // Without the rook on d8 the king escapes the double check
+    assert_eq!(
+        status("4kb2/3p1p2/3N4/8/8/8/8/4R1K1 b - - 0 1"),
+        GameStatus::Ongoing
+    );
+
+    assert_eq!(
+        status("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+        GameStatus::Ongoing
+    );
+    assert_eq!(
+        status("k7/8/1Q6/8/8/8/8/7K b - - 0 1"),
+        GameStatus::Stalemate
+    );
+    assert_eq!(
+        status("k7/8/8/8/8/8/8/7K w - - 100 80"),
+        GameStatus::FiftyMoveRule
+    );
+}
+
+#[test]
+fn is_position_legal_test() {
+    use crate::bitboard::{
+        board::FullerBitBoard, hash::FullZobristTables, setup::parse_position, vision::MostlyBits,
+    };
+
+    let legal = |fen| {
+        let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(fen).unwrap();
+        is_position_legal::<_, MostlyBits>(&board)
+    };
+
+    assert!(legal(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    ));
+    assert!(legal("4k3/8/8/8/8/8/8/4R1K1 b - - 0 1"));
+    assert!(!legal("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1"));
+}
+
+#[test]
+fn repetition_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy, board::FullerBitBoard, hash::FullZobristTables,
+        movegen::LegalBlessing, moving::clone_make_legal_move, san::resolve_san,
+        setup::parse_position, vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+    type ZT = FullZobristTables;
+
+    let key = |fen| {
+        let (board, _, _) = parse_position::<FullerBitBoard, ZT>(fen).unwrap();
+        repetition_key::<_, MostlyBits, Legal, ZT>(&board)
+    };
+
+    // No pawn to capture with
+    assert_eq!(
+        key("4k3/8/8/3p4/8/8/8/4K3 w - d6 0 1"),
+        key("4k3/8/8/3p4/8/8/8/4K3 w - - 0 1")
+    );
+    // The capturing pawn is pinned along the rank
+    assert_eq!(
+        key("8/8/8/K2pP2q/8/8/8/7k w - d6 0 1"),
+        key("8/8/8/K2pP2q/8/8/8/7k w - - 0 1")
+    );
+    assert_ne!(
+        key("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1"),
+        key("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1")
+    );
+
+    // After ...d5 exd6 is possible, so shuffling the knights back twice
+    // only repeats the position once.
+    let (mut board, _, _) = parse_position::<FullerBitBoard, ZT>(
+        "rnbqkbnr/pppppppp/8/4P3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+    )
+    .unwrap();
+    let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+    let mut keys = vec![];
+    for san in std::iter::once("d5").chain(shuffle.repeat(2)) {
+        let mv = resolve_san::<_, MostlyBits, Legal>(&board, san).unwrap();
+        board = clone_make_legal_move::<_, ZT>(&board, mv);
+        keys.push(repetition_key::<_, MostlyBits, Legal, ZT>(&board));
+        assert!(!is_threefold_repetition(&keys), "after {san}");
+    }
+    assert_ne!(keys[0], keys[4]);
+    assert_eq!(keys[4], keys[8]);
+
+    keys.push(keys[4]);
+    assert!(is_threefold_repetition(&keys));
+}
@@ -0,0 +1,156 @@
+//! Test suites of EPD positions.
+//!
+//! Tactics suites list the best moves of each position with the `bm`
+//! operation in SAN, and name the position with `id` or a `c0` comment.
+
+use chumsky::Parser;
+
+use crate::{
+    bitboard::{
+        board::BitBoard, hash::ZobristTables, movegen::BlessingStrategy, movegen::enumerate,
+        san::san, vision::Panopticon,
+    },
+    model::*,
+    notation::{Parsable, fen::epd::EpdBoard},
+};
+
+/// A position for which the solver did not find a best move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuiteFailure {
+    /// The `id` or `c0` operand of the position, or else the EPD itself.
+    pub name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SuiteReport {
+    pub passed: usize,
+    pub failures: Vec<SuiteFailure>,
+}
+
+impl SuiteReport {
+    pub fn all_passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Run the solver on every position and compare its move to the `bm` operands.
+///
+/// Moves are compared in SAN, ignoring check and annotation suffixes.
+pub fn run_epd_suite<BB, X, L, ZT>(epds: &[&str], solver: impl Fn(&BB) -> ChessMove) -> SuiteReport
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    ZT: ZobristTables,
+{
+    let mut report = SuiteReport::default();
+
+    for epd in epds {
+        match solve::<BB, X, L, ZT>(epd, &solver) {
+            Ok(()) => report.passed += 1,
+            Err((name, reason)) => report.failures.push(SuiteFailure { name, reason }),
+        }
+    }
+
+    report
+}
+
+fn solve<BB, X, L, ZT>(epd: &str, solver: impl Fn(&BB) -> ChessMove) -> Result<(), (String, String)>
+where
+    BB: BitBoard,
+    X: Panopticon,
+    L: BlessingStrategy<Blessing = LegalMove>,
+    ZT: ZobristTables,
+{
+    let parsed = EpdBoard::parser()
+        .parse(epd)
+        .into_result()
+        .map_err(|errs| {
+            let errs = errs.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+            (epd.to_string(), errs.join("; "))
+        })?;
+
+    let name = parsed
+        .operands("id")
+        .or_else(|| parsed.operands("c0"))
+        .and_then(|ops| ops.first())
+        .cloned()
+        .unwrap_or_else(|| epd.to_string());
+
+    let Some(best) = parsed.operands("bm") else {
+        return Err((name, "no bm operation".to_string()));
+    };
+
+    let board = parsed.board.as_bitboard::<BB, ZT>();
+    let mv = solver(&board);
+
+    let mut moves = vec![];
+    enumerate::<BB, X, L>(&board, &mut moves);
+    let Some(legal) = moves.into_iter().find(|lm| lm.0.same_move(mv)) else {
+        return Err((name, format!("{mv} is illegal")));
+    };
+
+    let found = san::<BB, X, L>(&board, legal).to_string();
+    let strip = |s: &str| s.trim_end_matches(['+', '#', '!', '?']).to_string();
+
+    if best.iter().any(|bm| strip(bm) == strip(&found)) {
+        Ok(())
+    } else {
+        Err((
+            name,
+            format!("played {found}, expected {}", best.join(" or ")),
+        ))
+    }
+}
+
+#[test]
+fn run_epd_suite_test() {
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy,
+        board::FullerBitBoard,
+        hash::{FullZobristTables, NoHashes},
+        movegen::LegalBlessing,
+        moving::clone_make_legal_move,
+        status::{GameStatus, game_status},
+        vision::MostlyBits,
+    };
+
+    type Legal = LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>;
+
+    let epds = [
+        r#"6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; id "back rank";"#,
+        r#"rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq g3 bm Qh4#; c0 "fool's mate";"#,
+    ];
+
+    let mate_in_one = |board: &FullerBitBoard| {
+        let mut moves = vec![];
+        enumerate::<FullerBitBoard, MostlyBits, Legal>(board, &mut moves);
+        moves
+            .into_iter()
+            .find(|&mv| {
+                let after = clone_make_legal_move::<FullerBitBoard, NoHashes>(board, mv);
+                game_status::<FullerBitBoard, MostlyBits, Legal>(&after) == GameStatus::Checkmate
+            })
+            .unwrap()
+            .0
+    };
+
+    let report =
+        run_epd_suite::<FullerBitBoard, MostlyBits, Legal, FullZobristTables>(&epds, mate_in_one);
+    assert!(report.all_passed(), "{report:?}");
+    assert_eq!(report.passed, 2);
+
+    let first_move = |board: &FullerBitBoard| {
+        let mut moves = vec![];
+        enumerate::<FullerBitBoard, MostlyBits, Legal>(board, &mut moves);
+        moves[0].0
+    };
+
+    let report = run_epd_suite::<FullerBitBoard, MostlyBits, Legal, FullZobristTables>(
+        &epds[..1],
+        first_move,
+    );
+    assert_eq!(report.passed, 0);
+    assert_eq!(report.failures[0].name, "back rank");
+}
@@ -1,5 +1,7 @@
 use std::{clone, iter::Map, ops::Deref};
 
+use crate::model::{BoardFile, BoardRank, Square};
+
 #[inline]
 pub fn bitor_sum<const N: usize>(data: &[u64; N]) -> u64 {
     let mut res = 0;
@@ -32,6 +34,40 @@ pub trait SliceExtensions<T>: Deref<Target = [T]> {
 
 impl<T, S: Deref<Target = [T]>> SliceExtensions<T> for S {}
 
+/// The masks of the files, from the a-file to the h-file.
+pub const FILES: [u64; 8] = {
+    let mut res = [0; 8];
+    let mut i = 0;
+    while i < 8 {
+        res[i] = 0x0101_0101_0101_0101 << i;
+        i += 1;
+    }
+    res
+};
+
+/// The masks of the ranks, from the first rank to the eighth.
+pub const RANKS: [u64; 8] = {
+    let mut res = [0; 8];
+    let mut i = 0;
+    while i < 8 {
+        res[i] = 0xFF << (8 * i);
+        i += 1;
+    }
+    res
+};
+
+/// The mask of a file.
+#[inline]
+pub fn file_mask(file: BoardFile) -> u64 {
+    FILES[file.ix()]
+}
+
+/// The mask of a rank.
+#[inline]
+pub fn rank_mask(rank: BoardRank) -> u64 {
+    0xFF << rank.ix()
+}
+
 #[macro_export]
 macro_rules! biterate {
     {for $sq:ident in $mask:expr; $body:tt } => {{
@@ -56,3 +92,67 @@ fn biterate_test() {
 
     println!("{:?}", v);
 }
+
+/// The squares of a mask, in ascending order from a1 to h8.
+pub fn squares_of(mask: u64) -> Vec<Square> {
+    let mut res = Vec::with_capacity(mask.count_ones() as usize);
+    biterate! {for sq in mask; {
+        res.push(sq);
+    }}
+    res
+}
+
+/// The squares of a mask as space-separated coordinates, like `a1 b2 e5`, for compact logging.
+pub fn mask_to_string(mask: u64) -> String {
+    squares_of(mask)
+        .iter()
+        .map(Square::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn mask_to_string_test() {
+    use crate::{
+        bitboard::{
+            board::{BitBoard, ChessBoard, FullerBitBoard},
+            hash::NoHashes,
+        },
+        model::{ChessColor, ChessPiece},
+    };
+
+    let board = FullerBitBoard::startpos::<NoHashes>();
+    let pawns = board.men(ChessColor::WHITE, ChessPiece::PAWN);
+
+    assert_eq!(squares_of(pawns).len(), 8);
+    assert_eq!(mask_to_string(pawns), "a2 b2 c2 d2 e2 f2 g2 h2");
+    assert_eq!(
+        mask_to_string(1 << Square::a1.ix() | 1 << Square::b2.ix() | 1 << Square::e5.ix()),
+        "a1 b2 e5"
+    );
+    assert_eq!(mask_to_string(0), "");
+}
+
+#[test]
+fn file_rank_mask_test() {
+    let a_file = file_mask(BoardFile::a_);
+    assert_eq!(a_file.count_ones(), 8);
+    assert!(
+        squares_of(a_file)
+            .iter()
+            .all(|sq| sq.coords().0 == BoardFile::a_)
+    );
+
+    assert_eq!(rank_mask(BoardRank::_1), 0xFF);
+    assert_eq!(rank_mask(BoardRank::_8), 0xFF00_0000_0000_0000);
+    assert_eq!(FILES.iter().fold(0, |acc, f| acc | f), !0);
+    assert_eq!(RANKS.iter().fold(0, |acc, r| acc ^ r), !0);
+
+    for i in 0..8 {
+        assert_eq!(rank_mask(BoardRank::from_u8(i)), RANKS[i as usize]);
+        assert_eq!(
+            file_mask(BoardFile::from_u8(i)) & rank_mask(BoardRank::from_u8(i)),
+            1 << (9 * i)
+        );
+    }
+}
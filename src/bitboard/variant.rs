@@ -0,0 +1,183 @@
+//! Boards of other dimensions than 8x8.
+//!
+//! The bitboards of this crate rely on a board of 64 squares fitting
+//! in a `u64`. Variants such as Knighted Chess on 10x8 need more squares,
+//! so a [`VariantBoard`] stores each bitboard as a sequence of `u64` chunks.
+//!
+//! Squares are indexed rank-major starting from the first rank,
+//! as in [`RectBoard`].
+
+use strum::VariantArray;
+
+use crate::notation::fen::{
+    generalized::{GenFenBoard, MAX_FILES, MAX_RANKS, RectBoard},
+    xtended::KnightedChessMan,
+};
+
+/// A board of arbitrary dimensions, with an occupancy bitboard
+/// for each kind of [`KnightedChessMan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantBoard {
+    files: usize,
+    ranks: usize,
+    men: [Vec<u64>; 16],
+}
+
+impl VariantBoard {
+    /// An empty board, failing if the dimensions are zero or
+    /// larger than [`MAX_FILES`] by [`MAX_RANKS`].
+    pub fn empty(files: usize, ranks: usize) -> Result<Self, String> {
+        if !(1..=MAX_FILES).contains(&files) {
+            Err(format!("{files} files is not within 1 to {MAX_FILES}"))?;
+        }
+
+        if !(1..=MAX_RANKS).contains(&ranks) {
+            Err(format!("{ranks} ranks is not within 1 to {MAX_RANKS}"))?;
+        }
+
+        let chunks = (files * ranks).div_ceil(64);
+
+        Ok(Self {
+            files,
+            ranks,
+            men: std::array::from_fn(|_| vec![0; chunks]),
+        })
+    }
+
+    /// Set up a board from a parsed G-FEN or X-FEN position.
+    pub fn from_gfen(gfen: &GenFenBoard<KnightedChessMan>) -> Result<Self, String> {
+        Self::from_rect(&gfen.rect_board()?)
+    }
+
+    /// Set up a board from a rectangle of chessmen.
+    pub fn from_rect(rect: &RectBoard<Option<KnightedChessMan>>) -> Result<Self, String> {
+        let mut res = Self::empty(rect.files(), rect.ranks())?;
+
+        for rank in 0..rect.ranks() {
+            for file in 0..rect.files() {
+                if let Some(&Some(man)) = rect.get(file, rank) {
+                    res.set(file, rank, Some(man));
+                }
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Number of files, i.e. the width of the board.
+    pub fn files(&self) -> usize {
+        self.files
+    }
+
+    /// Number of ranks, i.e. the height of the board.
+    pub fn ranks(&self) -> usize {
+        self.ranks
+    }
+
+    /// Index of a square, if it is on the board.
+    #[inline]
+    pub fn square(&self, file: usize, rank: usize) -> Option<usize> {
+        (file < self.files && rank < self.ranks).then_some(rank * self.files + file)
+    }
+
+    /// The bitboard of a kind of chessman, in chunks of 64 squares.
+    pub fn men(&self, man: KnightedChessMan) -> &[u64] {
+        &self.men[man.ix()]
+    }
+
+    /// All occupied squares, in chunks of 64 squares.
+    pub fn total(&self) -> Vec<u64> {
+        let mut res = vec![0; self.men[0].len()];
+        for bits in &self.men {
+            for (acc, chunk) in res.iter_mut().zip(bits) {
+                *acc |= chunk;
+            }
+        }
+        res
+    }
+
+    /// The chessman on a square, if any.
+    ///
+    /// # Panics
+    /// If the square is not on the board.
+    pub fn man_at(&self, file: usize, rank: usize) -> Option<KnightedChessMan> {
+        let (chunk, bit) = self.chunk_bit(file, rank);
+
+        KnightedChessMan::VARIANTS
+            .iter()
+            .copied()
+            .find(|man| self.men[man.ix()][chunk] & bit != 0)
+    }
+
+    /// Place a chessman on a square, or clear it.
+    ///
+    /// # Panics
+    /// If the square is not on the board.
+    pub fn set(&mut self, file: usize, rank: usize, man: Option<KnightedChessMan>) {
+        let (chunk, bit) = self.chunk_bit(file, rank);
+
+        for bits in &mut self.men {
+            bits[chunk] &= !bit;
+        }
+
+        if let Some(man) = man {
+            self.men[man.ix()][chunk] |= bit;
+        }
+    }
+
+    fn chunk_bit(&self, file: usize, rank: usize) -> (usize, u64) {
+        let Some(sq) = self.square(file, rank) else {
+            panic!(
+                "square ({file}, {rank}) is off the {}x{} board",
+                self.files, self.ranks
+            )
+        };
+        (sq / 64, 1 << (sq % 64))
+    }
+}
+
+#[test]
+fn capablanca_startpos_test() {
+    use chumsky::Parser;
+
+    use crate::notation::Parsable;
+    use KnightedChessMan::*;
+
+    let gfen = GenFenBoard::<KnightedChessMan>::parser()
+        .parse("rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1")
+        .into_result()
+        .unwrap();
+    let board = VariantBoard::from_gfen(&gfen).unwrap();
+
+    assert_eq!((board.files(), board.ranks()), (10, 8));
+    assert_eq!(board.men(WHITE_PAWN), &[0xFFC00, 0]);
+
+    let back_rank = [
+        WHITE_ROOK,
+        WHITE_KNIGHT,
+        WHITE_PRINCESS,
+        WHITE_BISHOP,
+        WHITE_QUEEN,
+        WHITE_KING,
+        WHITE_BISHOP,
+        WHITE_EMPRESS,
+        WHITE_KNIGHT,
+        WHITE_ROOK,
+    ];
+    for (file, &man) in back_rank.iter().enumerate() {
+        assert_eq!(board.man_at(file, 0), Some(man));
+        assert_eq!(board.man_at(file, 1), Some(WHITE_PAWN));
+        assert_eq!(board.man_at(file, 4), None);
+        assert_eq!(board.man_at(file, 6), Some(BLACK_PAWN));
+        assert_eq!(board.man_at(file, 7).map(|m| m as i8), Some(-(man as i8)));
+    }
+
+    let total = board.total();
+    assert_eq!(total.iter().map(|c| c.count_ones()).sum::<u32>(), 40);
+    assert_eq!(board.square(10, 0), None);
+    assert_eq!(board.square(9, 7), Some(79));
+
+    assert!(VariantBoard::empty(0, 8).is_err());
+    assert!(VariantBoard::empty(27, 8).is_err());
+    assert!(VariantBoard::empty(8, 101).is_err());
+}
@@ -1,5 +1,5 @@
 use crate::model::*;
-use std::marker::PhantomData;
+use std::{cell::RefCell, marker::PhantomData};
 
 use crate::{
     bitboard::binary::{
@@ -21,6 +21,17 @@ pub type MostlyBits = SimplePanopticon<
     KingDumbfill,
 >;
 
+/// As [`MostlyBits`], but with the slider attacks memoized in a [`SliderCache`].
+pub type CachedBits = SimplePanopticon<
+    PawnsBitBlit<true>,
+    PawnsBitBlit<false>,
+    KnightDumbfill,
+    CachedVision<FastObsDiffBishop>,
+    CachedVision<FastObsDiffRook>,
+    CachedVision<FastObsDiffQueen>,
+    KingDumbfill,
+>;
+
 #[derive(Debug, Clone, Copy)]
 pub struct SimplePanopticon<WhitePawn, BlackPawn, Knight, Bishop, Rook, Queen, King>(
     u64,
@@ -275,3 +286,176 @@ impl Vision for KingDumbfill {
 impl PieceVision for KingDumbfill {
     const ID: ChessPiece = ChessPiece::KING;
 }
+
+/// Memoizes the squares seen by another piece vision in the [`SliderCache`]
+/// of the current thread, keyed by the square and the occupancy.
+///
+/// Only worthwhile for sliders, whose vision depends on the occupancy.
+#[derive(Clone, Copy, Debug)]
+pub struct CachedVision<V: PieceVision> {
+    total: u64,
+    vision: V,
+}
+
+impl<V: PieceVision> Vision for CachedVision<V> {
+    #[inline]
+    fn new(total: u64) -> Self {
+        Self {
+            total,
+            vision: V::new(total),
+        }
+    }
+
+    #[inline]
+    fn see(self, sq: Square) -> u64 {
+        SLIDER_CACHE.with_borrow_mut(|cache| {
+            cache.get_or_insert(V::ID, sq, self.total, || self.vision.see(sq))
+        })
+    }
+}
+
+impl<V: PieceVision> PieceVision for CachedVision<V> {
+    const ID: ChessPiece = V::ID;
+}
+
+thread_local! {
+    static SLIDER_CACHE: RefCell<SliderCache> = RefCell::new(SliderCache::new());
+}
+
+/// A direct-mapped cache of slider visions, in which a new entry
+/// simply replaces whichever entry its key hashes to.
+pub struct SliderCache {
+    entries: Vec<SliderCacheEntry>,
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Clone, Copy, Default)]
+struct SliderCacheEntry {
+    total: u64,
+    /// The square and echelon, with the top bit set for occupied entries.
+    key: u16,
+    seen: u64,
+}
+
+impl SliderCache {
+    /// The number of entries, a power of two.
+    pub const SIZE: usize = 1 << 12;
+
+    fn new() -> Self {
+        Self {
+            entries: vec![SliderCacheEntry::default(); Self::SIZE],
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    fn get_or_insert(
+        &mut self,
+        ech: ChessPiece,
+        sq: Square,
+        total: u64,
+        see: impl FnOnce() -> u64,
+    ) -> u64 {
+        let key = 0x8000 | (ech.ix() as u16) << 6 | sq.ix() as u16;
+        let hash = (total ^ (key as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+            .wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        let entry = &mut self.entries[(hash >> (64 - Self::SIZE.trailing_zeros())) as usize];
+
+        if entry.key == key && entry.total == total {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+            *entry = SliderCacheEntry {
+                total,
+                key,
+                seen: see(),
+            };
+        }
+
+        entry.seen
+    }
+
+    /// The number of cache hits and misses on the current thread so far.
+    pub fn stats() -> (u64, u64) {
+        SLIDER_CACHE.with_borrow(|cache| (cache.hits, cache.misses))
+    }
+}
+
+#[test]
+fn cached_vision_test() {
+    use rand::{Rng, SeedableRng, rngs::SmallRng};
+
+    let mut rng = SmallRng::seed_from_u64(2181);
+    let totals: Vec<u64> = (0..8)
+        .map(|_| rng.random::<u64>() & rng.random::<u64>())
+        .collect();
+
+    let (hits, misses) = SliderCache::stats();
+
+    for _ in 0..5000 {
+        let total = totals[rng.random_range(0..totals.len())];
+        let sq = Square::from_u8(rng.random_range(0..64));
+
+        let (mb, cb) = (MostlyBits::new(total), CachedBits::new(total));
+        assert_eq!(mb.rook().see(sq), cb.rook().see(sq));
+        assert_eq!(mb.bishop().see(sq), cb.bishop().see(sq));
+        assert_eq!(mb.queen().see(sq), cb.queen().see(sq));
+    }
+
+    let (new_hits, new_misses) = SliderCache::stats();
+    assert_eq!(new_hits + new_misses - hits - misses, 15000);
+    assert!(new_hits - hits > 10000, "{} hits", new_hits - hits);
+
+    use crate::bitboard::{
+        attacking::FakeMoveSimplStrategy,
+        board::FullerBitBoard,
+        hash::FullZobristTables,
+        movegen::{LegalBlessing, enumerate},
+        setup::parse_position,
+    };
+
+    let (board, _, _) = parse_position::<FullerBitBoard, FullZobristTables>(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    )
+    .unwrap();
+    let (mut mostly, mut cached) = (vec![], vec![]);
+    enumerate::<_, MostlyBits, LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>>(
+        &board,
+        &mut mostly,
+    );
+    enumerate::<_, CachedBits, LegalBlessing<FakeMoveSimplStrategy<CachedBits>>>(
+        &board,
+        &mut cached,
+    );
+    assert_eq!(mostly, cached);
+}
+
+#[test]
+fn panopticon_delegation_test() {
+    fn check<X: Panopticon>() {
+        let total = 0x0042_1800_2400_8100u64;
+        let pan = X::new(total);
+
+        // Black pawns attack towards the first rank, white pawns towards the eighth
+        assert_eq!(
+            pan.black_pawn().see(Square::e4),
+            1 << Square::d3 as u8 | 1 << Square::f3 as u8
+        );
+        assert_eq!(
+            pan.white_pawn().see(Square::e4),
+            1 << Square::d5 as u8 | 1 << Square::f5 as u8
+        );
+        assert_eq!(pan.black_pawn().see(Square::a1), 0);
+
+        for sq in [Square::a1, Square::d4, Square::e5, Square::h8, Square::b7] {
+            assert_eq!(
+                pan.queen().see(sq),
+                pan.rook().see(sq) | pan.bishop().see(sq)
+            );
+        }
+    }
+
+    check::<MostlyBits>();
+    check::<CachedBits>();
+}
@@ -24,7 +24,7 @@ fn main_perft() {
         LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>,
         CloneMake,
         FullZobristTables,
-    >(5, false, ())
+    >(5, false, false, ())
     .pretty_print();
 
     println!("\nFull:");
@@ -34,7 +34,7 @@ fn main_perft() {
         LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>,
         CloneMake,
         FullZobristTables,
-    >(5, false, ())
+    >(5, false, false, ())
     .pretty_print();
 
     println!("\nCompact:");
@@ -44,7 +44,7 @@ fn main_perft() {
         LegalBlessing<FakeMoveSimplStrategy<MostlyBits>>,
         CloneMake,
         FullZobristTables,
-    >(5, false, ())
+    >(5, false, false, ())
     .pretty_print();
 }
 
@@ -164,6 +164,14 @@ pub enum ChessMan {
     WHITE_KING = 6,
 }
 
+impl ChessMan {
+    /// The chessman of a given color and echelon.
+    #[inline]
+    pub fn new(color: ChessColor, ech: ChessPiece) -> Self {
+        unsafe { std::mem::transmute(ech as i8 * color.sign()) }
+    }
+}
+
 /// Representation of color of a player or chessman.
 ///
 /// The choice here to not to mirror the convention of black = `-1` and
@@ -320,6 +328,15 @@ impl ChessOfficer {
     pub fn ix(self) -> usize {
         self as usize - 1
     }
+
+    #[inline]
+    pub fn from_piece(ech: ChessPiece) -> Option<Self> {
+        if ech == ChessPiece::PAWN {
+            None
+        } else {
+            unsafe { std::mem::transmute::<u8, Option<Self>>(ech as u8) }
+        }
+    }
 }
 
 /// Representation of the chess commoners, that is, not kings.
@@ -575,13 +592,16 @@ pub struct ChessMove {
 }
 
 impl ChessMove {
-    /// Sanity check for enumerated moves for standard chess.
+    /// Sanity check for enumerated moves of `player` under the given castling rules.
     ///
     /// Checks the following:
     ///
     /// - The start and end squares are different.
     /// - A castling move is a king move that doesn't capture.
     /// - A castling move is always contained to one rank.
+    /// - A castling move starts on the king's starting square, and ends on
+    ///   the king's ending square, or the rook's starting square if castling
+    ///   is written as capturing one's own rook.
     /// - A promotion is a pawn move.
     /// - A pawn-special move is a pawn move.
     /// - A pawn-special capture always captures a pawn.
@@ -593,11 +613,19 @@ impl ChessMove {
     ///
     /// - Bishops always move diagonally
     /// -
-    pub fn sanity_check(self) {
-        if CastlingDirection::from_special(self.special).is_some() {
+    pub fn sanity_check(self, player: ChessColor, rules: &CastlingRules) {
+        if let Some(dir) = CastlingDirection::from_special(self.special) {
             assert_eq!(self.ech, ChessPiece::KING);
             assert_eq!(self.capture, None);
-            assert_eq!(self.from as u8 & 0x7, self.to as u8 & 0x7);
+            assert_eq!(self.from as u8 & 0x38, self.to as u8 & 0x38);
+
+            let end = if rules.capture_own_rook {
+                rules.rook_start[player.ix()][dir.ix()]
+            } else {
+                rules.king_end[player.ix()][dir.ix()]
+            };
+            assert_eq!(self.from, rules.king_start[player.ix()]);
+            assert_eq!(self.to, end);
         }
 
         if ChessPawn::from_special(self.special).is_some() {
@@ -615,6 +643,210 @@ impl ChessMove {
 
         assert_ne!(self.from, self.to)
     }
+
+    /// Does this move reset the half-move clock of the 50-move rule?
+    ///
+    /// Per FIDE article 9.3, these are the captures and the pawn moves.
+    pub fn resets_halfmove_clock(self) -> bool {
+        self.ech == ChessPiece::PAWN || self.capture.is_some()
+    }
+
+    /// Is this a pawn capturing onto the back rank and promoting?
+    pub fn is_promotion_capture(self) -> bool {
+        self.capture.is_some() && PawnPromotion::from_special(self.special).is_some()
+    }
+
+    /// The square skipped by a pawn double push, which becomes the
+    /// en-passant square of the FEN after this move, if it is one.
+    pub fn creates_en_passant(self) -> Option<Square> {
+        (self.ech == ChessPiece::PAWN && (self.from as u8).abs_diff(self.to as u8) == 16)
+            .then(|| Square::from_u8((self.from as u8).min(self.to as u8) + 8))
+    }
+
+    /// Can the position before this move never recur after it?
+    ///
+    /// - Pawn moves and captures, as they reset the half-move clock,
+    ///   see [`ChessMove::resets_halfmove_clock`].
+    /// - Castling, as castling rights are part of the position for
+    ///   the purposes of repetition (FIDE article 9.2.3) and are lost
+    ///   permanently.
+    ///
+    /// King and rook moves which forfeit castling rights are also
+    /// irreversible in this sense, but this cannot be determined without
+    /// knowing the castling rights of the position.
+    pub fn is_irreversible(self) -> bool {
+        self.resets_halfmove_clock() || CastlingDirection::from_special(self.special).is_some()
+    }
+
+    /// Do these describe the same physical move?
+    ///
+    /// Only the origin, destination, and promotion are compared. The echelon and
+    /// capture are implied by the position, and the castling and pawn special moves
+    /// are implied by the origin and destination, so these are ignored, as they may
+    /// be missing or stale in moves not produced by move generation.
+    pub fn same_move(self, other: ChessMove) -> bool {
+        self.from == other.from
+            && self.to == other.to
+            && PawnPromotion::from_special(self.special)
+                == PawnPromotion::from_special(other.special)
+    }
+}
+
+#[test]
+fn creates_en_passant_test() {
+    let pawn = |from, to, special| ChessMove {
+        ech: ChessPiece::PAWN,
+        from,
+        to,
+        special,
+        capture: None,
+    };
+
+    assert_eq!(
+        pawn(Square::e2, Square::e4, Some(SpecialMove::PAWN)).creates_en_passant(),
+        Some(Square::e3)
+    );
+    assert_eq!(
+        pawn(Square::d7, Square::d5, Some(SpecialMove::PAWN)).creates_en_passant(),
+        Some(Square::d6)
+    );
+    assert_eq!(
+        pawn(Square::e2, Square::e3, None).creates_en_passant(),
+        None
+    );
+
+    let rook = ChessMove {
+        ech: ChessPiece::ROOK,
+        ..pawn(Square::a1, Square::a3, None)
+    };
+    assert_eq!(rook.creates_en_passant(), None);
+}
+
+/// Chess960 starting array RKNBBQNR, castling written as capturing one's own rook
+#[cfg(test)]
+pub(crate) const CHESS_960_RKNBBQNR: CastlingRules = CastlingRules {
+    rook_start: [[Square::a1, Square::h1], [Square::a8, Square::h8]],
+    rook_end: [[Square::d1, Square::f1], [Square::d8, Square::f8]],
+    king_start: [Square::b1, Square::b8],
+    king_end: [[Square::c1, Square::g1], [Square::c8, Square::g8]],
+    capture_own_rook: true,
+};
+
+#[test]
+fn castling_sanity_check_test() {
+    let castle = |from, to, dir| ChessMove {
+        ech: ChessPiece::KING,
+        from,
+        to,
+        special: Some(SpecialMove::from(dir)),
+        capture: None,
+    };
+
+    castle(Square::e1, Square::g1, CastlingDirection::WEST)
+        .sanity_check(ChessColor::WHITE, &CastlingRules::STANDARD);
+    castle(Square::e8, Square::c8, CastlingDirection::EAST)
+        .sanity_check(ChessColor::BLACK, &CastlingRules::STANDARD);
+
+    castle(Square::b1, Square::h1, CastlingDirection::WEST)
+        .sanity_check(ChessColor::WHITE, &CHESS_960_RKNBBQNR);
+}
+
+#[test]
+#[should_panic]
+fn castling_sanity_check_wrong_rook_test() {
+    ChessMove {
+        ech: ChessPiece::KING,
+        from: Square::b1,
+        to: Square::g1,
+        special: Some(SpecialMove::from(CastlingDirection::WEST)),
+        capture: None,
+    }
+    .sanity_check(ChessColor::WHITE, &CHESS_960_RKNBBQNR);
+}
+
+#[test]
+fn irreversible_move_test() {
+    let knight = ChessMove {
+        ech: ChessPiece::KNIGHT,
+        from: Square::g1,
+        to: Square::f3,
+        special: None,
+        capture: None,
+    };
+    assert!(!knight.is_irreversible());
+
+    let capture = ChessMove {
+        capture: Some(ChessCommoner::PAWN),
+        to: Square::e5,
+        ..knight
+    };
+    assert!(capture.is_irreversible());
+    assert!(capture.resets_halfmove_clock());
+
+    let push = ChessMove {
+        ech: ChessPiece::PAWN,
+        from: Square::e2,
+        to: Square::e4,
+        special: Some(SpecialMove::PAWN),
+        capture: None,
+    };
+    assert!(push.is_irreversible());
+    assert!(push.resets_halfmove_clock());
+
+    let castling = ChessMove {
+        ech: ChessPiece::KING,
+        from: Square::e1,
+        to: Square::g1,
+        special: Some(SpecialMove::WEST),
+        capture: None,
+    };
+    assert!(castling.is_irreversible());
+    assert!(!castling.resets_halfmove_clock());
+}
+
+#[test]
+fn same_move_test() {
+    let capture = ChessMove {
+        ech: ChessPiece::KNIGHT,
+        from: Square::f3,
+        to: Square::e5,
+        special: None,
+        capture: Some(ChessCommoner::PAWN),
+    };
+
+    let stale = ChessMove {
+        capture: Some(ChessCommoner::KNIGHT),
+        ..capture
+    };
+    assert!(stale.same_move(capture));
+    assert!(!stale.same_move(ChessMove {
+        to: Square::d4,
+        ..capture
+    }));
+
+    let castling = ChessMove {
+        ech: ChessPiece::KING,
+        from: Square::e1,
+        to: Square::g1,
+        special: Some(SpecialMove::WEST),
+        capture: None,
+    };
+    assert!(castling.same_move(ChessMove {
+        special: None,
+        ..castling
+    }));
+
+    let promotion = ChessMove {
+        ech: ChessPiece::PAWN,
+        from: Square::e7,
+        to: Square::e8,
+        special: Some(SpecialMove::QUEEN),
+        capture: None,
+    };
+    assert!(!promotion.same_move(ChessMove {
+        special: Some(SpecialMove::KNIGHT),
+        ..promotion
+    }));
 }
 
 /// The 'ply' identifier of a chess game.
@@ -634,7 +866,7 @@ pub struct Ply(
 
 impl Ply {
     /// Get the previous ply
-    fn prev(self) -> Self {
+    pub fn prev(self) -> Self {
         if self.1.is_white() {
             Self(self.0 - 1, ChessColor::BLACK)
         } else {
@@ -643,13 +875,70 @@ impl Ply {
     }
 
     /// Get the next ply
-    fn next(self) -> Self {
+    pub fn next(self) -> Self {
         if self.1.is_black() {
             Self(self.0 + 1, ChessColor::WHITE)
         } else {
             Self(self.0, ChessColor::BLACK)
         }
     }
+
+    /// The 0-based half-move index of this ply, i.e. the number of plies
+    /// played from the starting position, as opposed to the turn number of
+    /// FEN which counts full moves. For indexing into move lists.
+    pub fn index(self) -> u32 {
+        2 * (self.0 as u32 - 1) + self.1.is_black() as u32
+    }
+
+    /// The ply with the given index, see [`Ply::index`].
+    pub fn from_index(index: u32) -> Self {
+        let color = if index.is_multiple_of(2) {
+            ChessColor::WHITE
+        } else {
+            ChessColor::BLACK
+        };
+        Self((index / 2 + 1) as u16, color)
+    }
+}
+
+/// The FEN turn number and active player of a ply.
+pub fn fullmove_and_color(ply: Ply) -> (u16, ChessColor) {
+    let Ply(turn, color) = ply;
+    (turn, color)
+}
+
+/// The ply of a FEN turn number and active player, the inverse of [`fullmove_and_color`].
+pub fn from_fullmove_and_color(turn: u16, color: ChessColor) -> Ply {
+    Ply(turn, color)
+}
+
+#[test]
+fn ply_index_test() {
+    assert_eq!(Ply(1, ChessColor::WHITE).index(), 0);
+    assert_eq!(Ply(1, ChessColor::BLACK).index(), 1);
+    assert_eq!(Ply(2, ChessColor::WHITE).index(), 2);
+    assert_eq!(
+        fullmove_and_color(Ply::from_index(0)),
+        (1, ChessColor::WHITE)
+    );
+    assert_eq!(
+        fullmove_and_color(Ply::from_index(1)),
+        (1, ChessColor::BLACK)
+    );
+    assert_eq!(
+        fullmove_and_color(Ply::from_index(2)),
+        (2, ChessColor::WHITE)
+    );
+    assert_eq!(from_fullmove_and_color(40, ChessColor::BLACK).index(), 79);
+
+    let mut ply = Ply(1, ChessColor::WHITE);
+    for index in 0..200 {
+        assert_eq!(ply.index(), index);
+        assert_eq!(Ply::from_index(index), ply);
+        let (turn, color) = fullmove_and_color(ply);
+        assert_eq!(from_fullmove_and_color(turn, color), ply);
+        ply = ply.next();
+    }
 }
 
 /// Representations of the transient metadata of a chessboard.
@@ -695,6 +984,46 @@ impl Transients {
             rights: [[false; 2]; 2],
         }
     }
+
+    /// Transients from their fields, see also the `with_` methods to change one field.
+    pub fn new(en_passant: Option<EnPassant>, halfmove_clock: u8, rights: [[bool; 2]; 2]) -> Self {
+        Self {
+            en_passant,
+            halfmove_clock,
+            rights,
+        }
+    }
+
+    /// These transients with the given castling rights.
+    pub fn with_rights(self, rights: [[bool; 2]; 2]) -> Self {
+        Self { rights, ..self }
+    }
+
+    /// These transients with the given _en passant_ information.
+    pub fn with_en_passant(self, en_passant: Option<EnPassant>) -> Self {
+        Self { en_passant, ..self }
+    }
+
+    /// These transients with the given half-move clock.
+    pub fn with_clock(self, halfmove_clock: u8) -> Self {
+        Self {
+            halfmove_clock,
+            ..self
+        }
+    }
+
+    /// The transients of the board mirrored north-south with the colors swapped,
+    /// see [`Square::mirror_ns`].
+    pub fn mirror_ns(self) -> Self {
+        Self {
+            en_passant: self.en_passant.map(|ep| EnPassant {
+                square: ep.square.mirror_ns(),
+                capture: ep.capture.mirror_ns(),
+            }),
+            halfmove_clock: self.halfmove_clock,
+            rights: [self.rights[1], self.rights[0]],
+        }
+    }
 }
 
 /// Representation of the en-passant capture rule.
@@ -716,13 +1045,23 @@ pub struct EnPassant {
 }
 
 impl EnPassant {
+    /// The square the capturing pawn moves to, as written in FEN.
+    #[inline]
+    pub fn target_square(&self) -> Square {
+        self.square
+    }
+
+    /// The square of the pawn which is captured.
+    #[inline]
+    pub fn captured_square(&self) -> Square {
+        self.capture
+    }
+
+    /// The target square of an optional en-passant, as a bitmask and a square.
     #[inline]
     pub fn bit_sq(this: Option<Self>) -> (u64, Option<Square>) {
-        if let Some(this) = this {
-            (1 << this.square.ix(), Some(this.square))
-        } else {
-            (0, None)
-        }
+        let square = this.map(|ep| ep.target_square());
+        (square.map_or(0, |sq| 1 << sq.ix()), square)
     }
 }
 
@@ -793,3 +1132,59 @@ impl<T> DataBoard<T> {
         Self(array::from_fn(move |_| it()))
     }
 }
+
+#[test]
+fn transients_builder_test() {
+    let ep = EnPassant {
+        square: Square::d6,
+        capture: Square::d5,
+    };
+    let rights = [[false, true], [true, false]];
+
+    let trans = Transients::empty()
+        .with_rights(rights)
+        .with_en_passant(Some(ep))
+        .with_clock(7);
+    assert_eq!(trans.rights, rights);
+    assert_eq!(trans.en_passant, Some(ep));
+    assert_eq!(trans.halfmove_clock, 7);
+    assert_eq!(trans, Transients::new(Some(ep), 7, rights));
+
+    let trans = trans.with_en_passant(None).with_clock(0);
+    assert_eq!(trans.en_passant, None);
+    assert_eq!(trans.halfmove_clock, 0);
+    assert_eq!(trans.rights, rights);
+    assert_eq!(
+        Transients::startpos().with_rights([[false; 2]; 2]),
+        Transients::empty()
+    );
+}
+
+#[test]
+fn transients_mirror_ns_test() {
+    let mut rights = [[false; 2]; 2];
+    rights[ChessColor::WHITE.ix()][CastlingDirection::WEST.ix()] = true;
+
+    let trans = Transients {
+        en_passant: Some(EnPassant {
+            square: Square::e6,
+            capture: Square::e5,
+        }),
+        halfmove_clock: 0,
+        rights,
+    };
+
+    let mut mirrored_rights = [[false; 2]; 2];
+    mirrored_rights[ChessColor::BLACK.ix()][CastlingDirection::WEST.ix()] = true;
+
+    let mirrored = trans.mirror_ns();
+    assert_eq!(mirrored.rights, mirrored_rights);
+    assert_eq!(
+        mirrored.en_passant,
+        Some(EnPassant {
+            square: Square::e3,
+            capture: Square::e4,
+        })
+    );
+    assert_eq!(mirrored.mirror_ns(), trans);
+}
@@ -0,0 +1,149 @@
+//! # Extended Position Description
+//!
+//! EPD is a variant of FEN used for test suites and opening databases.
+//! It has the first four fields of FEN, followed by any number
+//! of operations, each of which is an opcode and some operands
+//! terminated by a semicolon:
+//!
+//! ```text
+//! 6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; id "back rank";
+//! ```
+//!
+//! Operands are either tokens without whitespace, or double-quoted strings.
+//! The half-move clock and turn number are given by the `hmvc` and `fmvn`
+//! operations if present.
+
+use chumsky::prelude::*;
+
+use crate::notation::{
+    Parsable, Prs,
+    fen::{FenBoard, fen_board, fen_color, fen_epc_square, generalized::gfen_castling, ws},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpdOperation {
+    pub opcode: String,
+    pub operands: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EpdBoard {
+    pub board: FenBoard,
+    pub operations: Vec<EpdOperation>,
+}
+
+impl EpdBoard {
+    /// The operands of the first operation with the given opcode.
+    pub fn operands(&self, opcode: &str) -> Option<&[String]> {
+        self.operations
+            .iter()
+            .find(|op| op.opcode == opcode)
+            .map(|op| op.operands.as_slice())
+    }
+}
+
+impl Parsable for EpdOperation {
+    fn parser<'s>() -> impl Prs<'s, Self> {
+        let opcode = any()
+            .filter(|c: &char| c.is_ascii_alphanumeric() || *c == '_')
+            .repeated()
+            .at_least(1)
+            .to_slice()
+            .labelled("opcode");
+
+        let quoted = none_of('"')
+            .repeated()
+            .to_slice()
+            .delimited_by(just('"'), just('"'));
+        let token = none_of(" \t;\"").repeated().at_least(1).to_slice();
+        let operand = choice((quoted, token)).map(str::to_string);
+
+        opcode
+            .map(str::to_string)
+            .then(ws().ignore_then(operand).repeated().collect())
+            .then_ignore(just(';'))
+            .map(|(opcode, operands)| Self { opcode, operands })
+            .boxed()
+    }
+}
+
+impl Parsable for EpdBoard {
+    fn parser<'s>() -> impl Prs<'s, Self> {
+        group((
+            fen_board().then_ignore(ws()),
+            fen_color().then_ignore(ws()),
+            gfen_castling().then_ignore(ws()),
+            fen_epc_square(),
+            ws().ignore_then(EpdOperation::parser())
+                .repeated()
+                .collect::<Vec<_>>(),
+        ))
+        .then_ignore(chumsky::text::whitespace())
+        .try_map(
+            |(board, to_move, castling_rights, en_passant, operations), span| {
+                let clock = |opcode, default: u16| {
+                    let Some(op) = operations.iter().find(|op| op.opcode == opcode) else {
+                        return Ok(default);
+                    };
+                    match op.operands.as_slice() {
+                        [n] => n.parse().map_err(|_| {
+                            Rich::custom(span, format!("unable to parse {opcode} {n}"))
+                        }),
+                        _ => Err(Rich::custom(span, format!("{opcode} takes one operand"))),
+                    }
+                };
+
+                let halfmove_clock = u8::try_from(clock("hmvc", 0)?)
+                    .map_err(|_| Rich::custom(span, "hmvc out of range for u8"))?;
+                let turn = clock("fmvn", 1)?;
+
+                Ok(Self {
+                    board: FenBoard::new(
+                        board,
+                        to_move,
+                        castling_rights,
+                        en_passant,
+                        halfmove_clock,
+                        turn,
+                    ),
+                    operations,
+                })
+            },
+        )
+        .boxed()
+    }
+}
+
+#[test]
+fn epd_parsing_test() {
+    use crate::model::ChessColor;
+
+    let epd = EpdBoard::parser()
+        .parse(r#"6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; id "back rank mate"; hmvc 3; fmvn 20;"#)
+        .into_result()
+        .unwrap();
+
+    assert_eq!(epd.board.to_move, ChessColor::WHITE);
+    assert_eq!(epd.board.halfmove_clock, 3);
+    assert_eq!(epd.board.turn, 20);
+    assert_eq!(epd.operands("bm"), Some(&["Ra8#".to_string()][..]));
+    assert_eq!(
+        epd.operands("id"),
+        Some(&["back rank mate".to_string()][..])
+    );
+    assert_eq!(epd.operands("am"), None);
+
+    let epd = EpdBoard::parser()
+        .parse("8/8/8/8/8/8/8/K6k b - -  ")
+        .into_result()
+        .unwrap();
+    assert!(epd.operations.is_empty());
+    assert_eq!(epd.board.turn, 1);
+
+    assert!(
+        EpdBoard::parser()
+            .parse("8/8/8/8/8/8/8/K6k b - - bm Kg1")
+            .into_result()
+            .is_err()
+    );
+}
@@ -33,6 +33,12 @@ use crate::{
 use chumsky::prelude::*;
 use chumsky::{IterParser, container::Seq, error::Rich};
 
+/// Largest number of files expressible in G-FEN, `a` through `z`.
+pub const MAX_FILES: usize = 26;
+
+/// Largest number of ranks accepted by the G-FEN parser.
+pub const MAX_RANKS: usize = 100;
+
 #[derive(Debug, Clone)]
 pub struct StdGenFenBoard {
     pub board: DataBoard<Option<ChessMan>>,
@@ -115,13 +121,105 @@ where
     }
 }
 
+impl<Man> GenFenBoard<Man>
+where
+    Man: Clone + Copy + Parsable + 'static,
+{
+    /// The board as a [`RectBoard`], failing if the ranks are ragged.
+    pub fn rect_board(&self) -> Result<RectBoard<Option<Man>>, String> {
+        let mut ranks = self.board.clone();
+        // G-FEN lists the ranks from the top down
+        ranks.reverse();
+        RectBoard::from_ranks(ranks)
+    }
+}
+
+/// A rectangular board of arbitrary dimensions.
+///
+/// Squares are stored rank-major, starting from the first rank,
+/// so that file 0 and rank 0 is the lower left corner as seen by white.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RectBoard<T> {
+    files: usize,
+    ranks: usize,
+    data: Vec<T>,
+}
+
+impl<T> RectBoard<T> {
+    /// Assemble a board from its ranks, starting from the first rank.
+    ///
+    /// All ranks must have the same, non-zero number of files.
+    pub fn from_ranks(ranks: Vec<Vec<T>>) -> Result<Self, String> {
+        let Some(files) = ranks.first().map(Vec::len) else {
+            Err("board has no ranks")?
+        };
+
+        if files == 0 {
+            Err("board has no files")?;
+        }
+
+        if let Some(r) = ranks.iter().position(|rank| rank.len() != files) {
+            Err(format!(
+                "rank {} has {} files, expected {files}",
+                r + 1,
+                ranks[r].len()
+            ))?;
+        }
+
+        Ok(Self {
+            files,
+            ranks: ranks.len(),
+            data: ranks.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Number of files, i.e. the width of the board.
+    pub fn files(&self) -> usize {
+        self.files
+    }
+
+    /// Number of ranks, i.e. the height of the board.
+    pub fn ranks(&self) -> usize {
+        self.ranks
+    }
+
+    /// Read a square, if it is on the board.
+    pub fn get(&self, file: usize, rank: usize) -> Option<&T> {
+        if file < self.files && rank < self.ranks {
+            self.data.get(rank * self.files + file)
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn rect_board_test() {
+    let board = GenFenBoard::<ColorCase<char>>::parser()
+        .parse("rnbqkbnrca/pppppppppp/10/10/10/10/PPPPPPPPPP/RNBQKBNRCA w - - 0 1")
+        .into_result()
+        .unwrap()
+        .rect_board()
+        .unwrap();
+
+    assert_eq!((board.files(), board.ranks()), (10, 8));
+    assert_eq!(board.get(0, 0), Some(&Some(ColorCase::White('R'))));
+    assert_eq!(board.get(9, 7), Some(&Some(ColorCase::Black('a'))));
+    assert_eq!(board.get(4, 4), Some(&None));
+    assert_eq!(board.get(10, 0), None);
+    assert_eq!(board.get(0, 8), None);
+
+    assert!(RectBoard::from_ranks(vec![vec![0; 10], vec![0; 9]]).is_err());
+    assert!(RectBoard::<u8>::from_ranks(vec![]).is_err());
+}
+
 impl<Man> Parsable for GenFenBoard<Man>
 where
     Man: Clone + Copy + Parsable + 'static,
 {
     fn parser<'s>() -> impl Prs<'s, Self> {
         group((
-            gfen_board(1..=26, 1..=100, Man::parser()).then_ignore(ws()),
+            gfen_board(1..=MAX_FILES, 1..=MAX_RANKS, Man::parser()).then_ignore(ws()),
             fen_color().then_ignore(ws()),
             gfen_castling().then_ignore(ws()),
             gfen_epc_square().then_ignore(ws()),
@@ -137,7 +235,7 @@ impl Parsable for ColorCase<char> {
     fn parser<'s>() -> impl Prs<'s, Self> {
         choice((
             one_of('a'..='z').map(Self::Black),
-            one_of('A'..='Z').map(Self::Black),
+            one_of('A'..='Z').map(Self::White),
         ))
         .boxed()
     }
@@ -146,7 +244,7 @@ impl Parsable for ColorCase<char> {
 pub(crate) fn gfen_epc_square<'s>() -> impl Prs<'s, Option<(char, u8)>> {
     choice((
         one_of('a'..='z')
-            .then(parse_usize(0..=100).map(|u| u as u8))
+            .then(parse_usize(0..=MAX_RANKS).map(|u| u as u8))
             .map(Some),
         just('-').to(None),
     ))
@@ -176,7 +274,9 @@ where
     ChessThing: Clone + 's,
 {
     gfen_board(8..=8, 8..=8, man)
-        .map(|v| {
+        .map(|mut v| {
+            // FEN lists the ranks from the 8th down to the 1st
+            v.reverse();
             let mut b = DataBoard::new(|| None);
             b.0.clone_from_slice(&v.concat());
             b
@@ -189,6 +289,29 @@ pub(crate) fn gfen_board<'s, ChessThing>(
     ranks: impl RangeBounds<usize> + 's,
     man: impl Prs<'s, ChessThing> + 's,
 ) -> impl Prs<'s, Vec<Vec<Option<ChessThing>>>>
+where
+    ChessThing: Clone + 's,
+{
+    gfen_ranks(ranks, gfen_line(files, man))
+}
+
+/// A lenient variant of [`gfen_board`] which additionally accepts
+/// a single `.` or space as an empty square, as found in ad-hoc text diagrams.
+pub(crate) fn gfen_board_dotted<'s, ChessThing>(
+    files: impl RangeBounds<usize> + 's,
+    ranks: impl RangeBounds<usize> + 's,
+    man: impl Prs<'s, ChessThing> + 's,
+) -> impl Prs<'s, Vec<Vec<Option<ChessThing>>>>
+where
+    ChessThing: Clone + 's,
+{
+    gfen_ranks(ranks, gfen_line_dotted(files, man))
+}
+
+fn gfen_ranks<'s, ChessThing>(
+    ranks: impl RangeBounds<usize> + 's,
+    line: impl Prs<'s, Vec<Option<ChessThing>>> + 's,
+) -> impl Prs<'s, Vec<Vec<Option<ChessThing>>>>
 where
     ChessThing: Clone + 's,
 {
@@ -204,8 +327,7 @@ where
         Bound::Unbounded => usize::MAX,
     };
 
-    gfen_line(files, man)
-        .separated_by(just('/').labelled("solidus (/)"))
+    line.separated_by(just('/').labelled("solidus (/)"))
         .at_least(at_least)
         .at_most(at_most)
         .collect()
@@ -239,6 +361,45 @@ where
     .boxed()
 }
 
+pub(crate) fn gfen_line_dotted<'s, ChessThing>(
+    range: impl RangeBounds<usize> + 's,
+    man: impl Prs<'s, ChessThing> + 's,
+) -> impl Prs<'s, Vec<Option<ChessThing>>>
+where
+    ChessThing: Clone + 's,
+{
+    let int_range = (Bound::Excluded(0), range.end_bound().copied());
+    choice((
+        man.map(|c| Some(c)).repeated().at_least(1).collect(),
+        parse_usize(int_range).map(|n| vec![None; n]),
+        one_of(". ").to(vec![None]),
+    ))
+    .repeated()
+    .collect::<Vec<_>>()
+    .map(|vv| vv.concat())
+    .filter(move |v| range.contains(&v.len()))
+    .boxed()
+}
+
+#[test]
+fn gfen_board_dotted_test() {
+    let dotted = gfen_board_dotted(8..=8, 8..=8, fen_chessman())
+        .parse("rnbqkbnr/pppppppp/......../8/..  ..../4..../PPPPPPPP/RNBQKBNR")
+        .into_result()
+        .unwrap();
+    let strict = gfen_board(8..=8, 8..=8, fen_chessman())
+        .parse("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR")
+        .into_result()
+        .unwrap();
+    assert_eq!(dotted, strict);
+
+    assert!(
+        gfen_board(8..=8, 8..=8, fen_chessman())
+            .parse("rnbqkbnr/pppppppp/......../8/8/8/PPPPPPPP/RNBQKBNR")
+            .has_errors()
+    );
+}
+
 #[test]
 fn gfen_line_test() {
     println!(
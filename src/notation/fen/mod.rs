@@ -45,11 +45,15 @@
 //! rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1
 //! ```
 
+pub mod epd;
 pub mod generalized;
 pub mod shredder;
 pub mod xtended;
 
-use std::{collections::HashSet, fmt::Display};
+use std::{
+    collections::HashSet,
+    fmt::{Display, Write},
+};
 
 use chumsky::{prelude::*, text::Char};
 
@@ -102,26 +106,57 @@ impl FenBoard {
             Err("duplicate in castling rights field")?;
         }
 
-        self.castling_check(ColorCase::White(CastlingDirection::EAST))?;
-        self.castling_check(ColorCase::Black(CastlingDirection::EAST))?;
-        self.castling_check(ColorCase::White(CastlingDirection::WEST))?;
-        self.castling_check(ColorCase::Black(CastlingDirection::WEST))?;
+        for &c in &self.castling_rights {
+            self.castling_check(c)?;
+        }
 
         self.epc_check()?;
 
+        self.count_check()?;
+
         return Ok(());
     }
 
-    fn epc_check(&self) -> Result<(), String> {
-        if let Some(sq) = self.en_passant {
-            match (self.to_move, sq.coords().1) {
-                (ChessColor::WHITE, BoardRank::_3) => return Ok(()),
-                (ChessColor::BLACK, BoardRank::_6) => return Ok(()),
-                _ => {}
+    fn count_check(&self) -> Result<(), String> {
+        let mut counts = [[0u8; 6]; 2];
+        for &man in self.board.0.iter().flatten() {
+            counts[ChessColor::from(man).ix()][ChessPiece::from(man).ix()] += 1;
+        }
+
+        for (color, counts) in [("white", counts[0]), ("black", counts[1])] {
+            let [pawns, knights, bishops, rooks, queens, _] = counts;
+
+            if pawns > 8 {
+                Err(format!("{color} has {pawns} pawns"))?;
+            }
+
+            let total: u8 = counts.iter().sum();
+            if total > 16 {
+                Err(format!("{color} has {total} chessmen"))?;
+            }
+
+            // Every officer beyond the starting complement is a promoted pawn
+            let promoted = knights.saturating_sub(2)
+                + bishops.saturating_sub(2)
+                + rooks.saturating_sub(2)
+                + queens.saturating_sub(1);
+            if promoted + pawns > 8 {
+                Err(format!(
+                    "{color} has {promoted} promoted officers and {pawns} pawns"
+                ))?;
             }
         }
 
-        Err("illegal en-passant square".to_string())
+        Ok(())
+    }
+
+    fn epc_check(&self) -> Result<(), String> {
+        match (self.to_move, self.en_passant.map(|sq| sq.coords().1)) {
+            (_, None) => Ok(()),
+            (ChessColor::WHITE, Some(BoardRank::_6)) => Ok(()),
+            (ChessColor::BLACK, Some(BoardRank::_3)) => Ok(()),
+            _ => Err("illegal en-passant square".to_string()),
+        }
     }
 
     fn castling_check(&self, c: ColorCase<CastlingDirection>) -> Result<(), String> {
@@ -186,21 +221,234 @@ impl Parsable for FenBoard {
     }
 }
 
+/// Error reading a FEN string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    /// The string is not FEN.
+    Syntax(String),
+    /// The string is FEN, but the position is not sane.
+    Position(String),
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax(e) => write!(f, "invalid FEN: {e}"),
+            Self::Position(e) => write!(f, "invalid position: {e}"),
+        }
+    }
+}
+
+/// Parse a FEN string and check that the position is sane.
+pub fn parse_fen(fen: &str) -> Result<FenBoard, FenError> {
+    let board = FenBoard::parser()
+        .parse(fen)
+        .into_result()
+        .map_err(|errs| {
+            FenError::Syntax(
+                errs.iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            )
+        })?;
+
+    board.sanity_check().map_err(FenError::Position)?;
+
+    Ok(board)
+}
+
+/// Lazily parse a FEN string on each non-blank line of the input.
+///
+/// Errors are prefixed with the line number, counting from 1.
+pub fn parse_fens(input: &str) -> impl Iterator<Item = Result<FenBoard, FenError>> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(ix, line)| {
+            parse_fen(line.trim()).map_err(|e| match e {
+                FenError::Syntax(e) => FenError::Syntax(format!("line {}: {e}", ix + 1)),
+                FenError::Position(e) => FenError::Position(format!("line {}: {e}", ix + 1)),
+            })
+        })
+}
+
+#[test]
+fn parse_fens_test() {
+    let input = "
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1
+r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1
+8/2p5/3p4/KP5r/1R3p1k/8/4P1P1 w - - 0 1
+
+8/8/8/8/8/8/8/K6k w - - 0 1
+";
+
+    let res = parse_fens(input).collect::<Vec<_>>();
+    assert_eq!(res.len(), 4);
+    assert!(res[0].is_ok());
+    assert!(res[1].is_ok());
+    assert!(matches!(&res[2], Err(FenError::Syntax(e)) if e.starts_with("line 4:")));
+    assert_eq!(res[3].as_ref().unwrap().to_move, ChessColor::WHITE);
+}
+
+/// The FEN letters of the chessmen, indexed by discriminant offset by 6.
+const FEN_CHESSMEN: &[u8; 13] = b"kqrbnp PNBRQK";
+
+/// Write the piece placement field of FEN, without the other fields.
+pub fn write_board_field(
+    f: &mut impl Write,
+    board: &DataBoard<Option<ChessMan>>,
+) -> std::fmt::Result {
+    for rank in (0..8).rev() {
+        let mut empty = 0;
+        for file in 0..8 {
+            let sq = Square::from_coords(BoardFile::from_u8(file), BoardRank::from_u8(rank));
+            if let Some(cm) = board.get(sq) {
+                if empty > 0 {
+                    write!(f, "{empty}")?;
+                    empty = 0;
+                }
+                f.write_char(FEN_CHESSMEN[(*cm as i8 + 6) as usize] as char)?;
+            } else {
+                empty += 1;
+            }
+        }
+        if empty > 0 {
+            write!(f, "{empty}")?;
+        }
+        if rank > 0 {
+            f.write_char('/')?;
+        }
+    }
+
+    Ok(())
+}
+
 impl Display for FenBoard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{} {} {} {} {} {}",
-            todo!(),
-            todo!(),
-            todo!(),
-            todo!(),
-            todo!(),
-            todo!(),
-        )
+        use CastlingDirection::*;
+        use ColorCase::*;
+
+        write_board_field(f, &self.board)?;
+
+        match self.to_move {
+            ChessColor::WHITE => f.write_str(" w ")?,
+            ChessColor::BLACK => f.write_str(" b ")?,
+        }
+
+        let mut any = false;
+        for (case, c) in
+            [(White(WEST), 'K'), (White(EAST), 'Q'), (Black(WEST), 'k'), (Black(EAST), 'q')]
+        {
+            if self.castling_rights.contains(&case) {
+                f.write_char(c)?;
+                any = true;
+            }
+        }
+        if !any {
+            f.write_char('-')?;
+        }
+
+        if let Some(sq) = self.en_passant {
+            write!(f, " {sq}")?;
+        } else {
+            f.write_str(" -")?;
+        }
+
+        write!(f, " {} {}", self.halfmove_clock, self.turn)
     }
 }
 
+/// A square whose occupant differs between two positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SquareChange {
+    pub square: Square,
+    pub before: Option<ChessMan>,
+    pub after: Option<ChessMan>,
+}
+
+/// Compare the chessboards of two FEN strings, listing the squares
+/// which differ in ascending square order.
+///
+/// Only the piece placement is compared, not the remaining fields.
+pub fn fen_diff(before: &str, after: &str) -> Result<Vec<SquareChange>, String> {
+    let parse = |fen| {
+        FenBoard::parser().parse(fen).into_result().map_err(|errs| {
+            errs.iter()
+                .map(|e| format!("{e} in {fen:?}"))
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+    };
+
+    let before = parse(before)?.board;
+    let after = parse(after)?.board;
+
+    Ok(before
+        .0
+        .iter()
+        .zip(after.0.iter())
+        .enumerate()
+        .filter(|(_, (b, a))| b != a)
+        .map(|(ix, (b, a))| SquareChange {
+            square: Square::from_u8(ix as u8),
+            before: *b,
+            after: *a,
+        })
+        .collect())
+}
+
+#[test]
+fn fen_diff_test() {
+    let changes = fen_diff(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+    )
+    .unwrap();
+
+    assert_eq!(
+        changes,
+        vec![
+            SquareChange {
+                square: Square::e2,
+                before: Some(ChessMan::WHITE_PAWN),
+                after: None,
+            },
+            SquareChange {
+                square: Square::e4,
+                before: None,
+                after: Some(ChessMan::WHITE_PAWN),
+            },
+        ]
+    );
+
+    assert!(fen_diff("8/8/8/8 w - - 0 1", "8/8/8/8/8/8/8/8 w - - 0 1").is_err());
+}
+
+#[test]
+fn fen_sanity_check_test() {
+    let check = |fen| {
+        FenBoard::parser()
+            .parse(fen)
+            .into_result()
+            .unwrap()
+            .sanity_check()
+    };
+
+    assert!(check("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_ok());
+    assert!(check("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").is_ok());
+    assert!(check("4k3/8/8/8/3P4/8/8/4K3 b - d3 0 1").is_ok());
+    assert!(check("4k3/8/8/8/3P4/8/8/4K3 w - d3 0 1").is_err());
+    assert!(check("4k3/8/8/8/8/8/8/4K3 w K - 0 1").is_err());
+
+    assert!(check("4k3/8/8/8/8/8/PPPPPPPP/4K2P w - - 0 1").is_err());
+    assert!(check("4k3/8/8/8/8/8/QQQQQQQQ/3QK3 w - - 0 1").is_ok());
+    assert!(check("4k3/8/8/8/8/P7/QQQQQQQQ/3QK3 w - - 0 1").is_err());
+    assert!(check("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1").is_ok());
+    assert!(check("rnbqkbnr/pppppppp/8/8/8/7N/PPPPPPPP/RNBQKBNR w - - 0 1").is_err());
+}
+
 fn ws<'s>() -> impl Prs<'s, ()> {
     chumsky::text::whitespace().at_least(1)
 }
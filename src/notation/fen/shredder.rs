@@ -74,8 +74,36 @@ impl Parsable for ColorCase<BoardFile> {
         use ColorCase::*;
         choice((
             BoardFile::parser().map(Black),
-            one_of('A'..='H').map(|c| White(BoardFile::from_u8((c as u32 - 'H' as u32) as u8))),
+            one_of('A'..='H').map(|c| White(BoardFile::from_u8((c as u32 - 'A' as u32) as u8))),
         ))
-        .labelled("on of A ... H, a ... h")
+        .labelled("one of A ... H, a ... h")
     }
 }
+
+#[test]
+fn shredder_fen_castling_test() {
+    use BoardFile::*;
+    use ColorCase::*;
+
+    let board = ShrFenBoard::parser()
+        .parse("rbbqknnr/pppppppp/8/8/8/8/PPPPPPPP/RBBQKNNR w HAha - 0 1")
+        .into_result()
+        .unwrap();
+    assert_eq!(
+        board.castling_rights,
+        vec![White(h_), White(a_), Black(h_), Black(a_)]
+    );
+
+    let board = ShrFenBoard::parser()
+        .parse("rbbqknnr/pppppppp/8/8/8/8/PPPPPPPP/RBBQKNNR w - - 0 1")
+        .into_result()
+        .unwrap();
+    assert!(board.castling_rights.is_empty());
+
+    assert!(
+        ShrFenBoard::parser()
+            .parse("rbbqknnr/pppppppp/8/8/8/8/PPPPPPPP/RBBQKNNR w KQkq - 0 1")
+            .into_result()
+            .is_err()
+    );
+}
@@ -181,6 +181,21 @@ pub enum KnightedChessMan {
     WHITE_KING = 8,
 }
 
+impl KnightedChessMan {
+    /// Use this chessman as an array index, black pieces first.
+    #[inline]
+    pub fn ix(self) -> usize {
+        let i = self as i8;
+        (if i < 0 { i + 8 } else { i + 7 }) as usize
+    }
+}
+
+impl Parsable for KnightedChessMan {
+    fn parser<'s>() -> impl Prs<'s, Self> {
+        xfen_knighted_chessman()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum KnightedCastlingFile {
     Side(CastlingDirection),
@@ -36,7 +36,7 @@ impl Display for BoardFile {
 
 impl Display for BoardRank {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(Self::VARIANTS[self.ix()])
+        f.write_str(Self::VARIANTS[*self as usize])
     }
 }
 
@@ -72,6 +72,62 @@ impl Display for CoordNotation {
     }
 }
 
+/// Coordinate notation as used by UCI, e.g. `e2e4` or `e7e8q`.
+///
+/// Castling is written as the two-square move of the king, unless the
+/// alternate flag (`{:#}`) is given, in which case it is written `O-O` or `O-O-O`.
+impl Display for ChessMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match CastlingDirection::from_special(self.special) {
+            Some(dir) if f.alternate() => match dir {
+                CastlingDirection::EAST => StdAlgCastling::OOO(None).fmt(f),
+                CastlingDirection::WEST => StdAlgCastling::OO(None).fmt(f),
+            },
+            _ => CoordNotation::from(*self).fmt(f),
+        }
+    }
+}
+
+#[test]
+fn chess_move_display_test() {
+    use crate::model::{ChessPiece, SpecialMove};
+
+    let mv = ChessMove {
+        ech: ChessPiece::PAWN,
+        from: Square::e2,
+        to: Square::e4,
+        special: Some(SpecialMove::PAWN),
+        capture: None,
+    };
+    assert_eq!(mv.to_string(), "e2e4");
+    assert_eq!(format!("{mv:#}"), "e2e4");
+
+    let mv = ChessMove {
+        from: Square::e7,
+        to: Square::e8,
+        special: Some(SpecialMove::QUEEN),
+        ..mv
+    };
+    assert_eq!(mv.to_string(), "e7e8q");
+
+    let mv = ChessMove {
+        ech: ChessPiece::KING,
+        from: Square::e1,
+        to: Square::g1,
+        special: Some(SpecialMove::WEST),
+        capture: None,
+    };
+    assert_eq!(mv.to_string(), "e1g1");
+    assert_eq!(format!("{mv:#}"), "O-O");
+
+    let mv = ChessMove {
+        to: Square::c1,
+        special: Some(SpecialMove::EAST),
+        ..mv
+    };
+    assert_eq!(format!("{mv:#}"), "O-O-O");
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum StdAlgNotation {
     Pawn(StdAlgPawn),
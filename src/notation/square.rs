@@ -18,7 +18,7 @@ impl Parsable for BoardFile {
 impl Parsable for BoardRank {
     fn parser<'s>() -> impl Prs<'s, Self> {
         one_of('1'..='8')
-            .map(|c| Self::from_u8((c as u32 - 'a' as u32) as u8))
+            .map(|c| Self::from_u8((c as u32 - '1' as u32) as u8))
             .labelled("a rank number 1 ... 8")
             .boxed()
     }